@@ -1,6 +1,9 @@
 use clap::Parser;
 use serde::Deserialize;
 use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::{bot::BotPreset, mode::GameMode};
 
 #[derive(Parser, Debug, Clone, Deserialize)]
 #[serde(default)]
@@ -21,6 +24,83 @@ pub struct Args {
 
     #[clap(short = 'P', long, value_parser)]
     pub password: String,
+
+    /// Scale of the HUD on top of the automatic scaling to the window size
+    #[clap(long, default_value = "1.0")]
+    pub ui_scale: f32,
+
+    /// Write gameplay events as JSON lines to this file
+    #[clap(long, value_parser)]
+    pub event_log: Option<PathBuf>,
+
+    /// Rules of the match played after the lobby
+    #[clap(long, value_enum, default_value = "elimination")]
+    pub mode: GameMode,
+
+    /// Rounds of an elimination match
+    #[clap(long, default_value = "3")]
+    pub rounds: u32,
+
+    /// Kills needed to win a deathmatch
+    #[clap(long, default_value = "10")]
+    pub score_limit: u32,
+
+    /// Seconds a ship has to hold the zone alone to win king of the hill
+    #[clap(long, default_value = "60")]
+    pub hill_score_limit: u32,
+
+    /// Length of a timed match in seconds, 0 lets a deathmatch or king of the
+    /// hill run until the score limit
+    #[clap(long, default_value = "300")]
+    pub time_limit: u64,
+
+    /// Score the energy held at the end of an orb race instead of orbs collected
+    #[clap(long)]
+    pub bank_energy: bool,
+
+    /// Ships per team, 2 or 3 for 2v2 or 3v3 matches
+    #[clap(long, default_value = "1")]
+    pub team_size: usize,
+
+    /// Team of every player slot in order, 0 for blue and 1 for red, e.g.
+    /// 0,1,1,0. Networked team matches need the same teams on every peer,
+    /// slots without one alternate between the teams
+    #[clap(long, value_delimiter = ',')]
+    pub teams: Vec<usize>,
+
+    /// Let ships damage their teammates
+    #[clap(long)]
+    pub friendly_fire: bool,
+
+    /// Fill this many player slots with bots
+    #[clap(long, default_value = "0")]
+    pub bots: usize,
+
+    /// Presets of the bot slots in order, the last one is used for the rest
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub bot_presets: Vec<BotPreset>,
+
+    /// Seed of the bots' aim errors, the same seed gives the same bots
+    #[clap(long, default_value = "0")]
+    pub bot_seed: u64,
+
+    /// Seed of random events all peers have to agree on, e.g. where the
+    /// capture zone moves, the same on every peer
+    #[clap(long, default_value = "0")]
+    pub seed: u64,
+
+    /// Run the headless projectile benchmark instead of the game
+    #[clap(long)]
+    pub bench_projectiles: bool,
+
+    /// Play headless bot matches between all ship classes and weapons and
+    /// write the results to this CSV file instead of running the game
+    #[clap(long)]
+    pub tournament: Option<PathBuf>,
+
+    /// Matches per pairing of loadouts in the tournament
+    #[clap(long, default_value = "10")]
+    pub tournament_matches: u32,
 }
 
 impl Default for Args {
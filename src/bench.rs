@@ -0,0 +1,110 @@
+//! Headless benchmark measuring frame time while ships fire continuously.
+//!
+//! Run with `cargo run --release -- -u bench -P bench --bench-projectiles`.
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    events::{DamageDealt, ShipDestroyed},
+    ship::{Energy, Ship},
+    weapon::{self, ProjectilePool, WeaponBundle, WeaponDefinition, WeaponPlugin},
+    GameState,
+};
+
+const SHIPS: usize = 8;
+const WARMUP_FRAMES: usize = 60;
+const FRAMES: usize = 600;
+
+pub fn run() {
+    for pooled in [false, true] {
+        let frame_times = measure(pooled);
+        report(pooled, &frame_times);
+    }
+}
+
+fn measure(pooled: bool) -> Vec<Duration> {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(AssetPlugin)
+        .add_asset::<Image>()
+        .add_state(GameState::Playing)
+        .add_event::<DamageDealt>()
+        .add_event::<ShipDestroyed>()
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+        .add_plugin(WeaponPlugin)
+        .insert_resource(ProjectilePool {
+            enabled: pooled,
+            ..Default::default()
+        })
+        .add_startup_system(spawn_ships);
+
+    app.world.resource_mut::<RapierConfiguration>().gravity = Vec2::ZERO;
+
+    for _ in 0..WARMUP_FRAMES {
+        app.update();
+    }
+
+    (0..FRAMES)
+        .map(|_| {
+            let start = Instant::now();
+            app.update();
+            start.elapsed()
+        })
+        .collect()
+}
+
+fn spawn_ships(mut commands: Commands, mut definitions: ResMut<Assets<WeaponDefinition>>) {
+    let definition = definitions.add(WeaponDefinition {
+        fire_rate: 30.0,
+        damage: 1.0,
+        ..Default::default()
+    });
+
+    for i in 0..SHIPS {
+        let angle = std::f32::consts::TAU * i as f32 / SHIPS as f32;
+        commands
+            .spawn()
+            .insert(Ship)
+            .insert(Energy {
+                max: f32::MAX,
+                current: f32::MAX,
+            })
+            .insert_bundle(TransformBundle::from_transform(
+                Transform::from_translation((Vec2::from_angle(angle) * 400.0).extend(0.0))
+                    .with_rotation(Quat::from_rotation_z(angle)),
+            ))
+            .insert(RigidBody::Fixed)
+            .insert(Velocity::default())
+            .insert(Collider::ball(50.0))
+            .with_children(|p| {
+                p.spawn_bundle(WeaponBundle {
+                    definition: definition.clone(),
+                    ..Default::default()
+                })
+                .insert(weapon::FireWeapon);
+            });
+    }
+}
+
+fn report(pooled: bool, frame_times: &[Duration]) {
+    let mut sorted = frame_times.to_vec();
+    sorted.sort();
+    let total: Duration = sorted.iter().sum();
+    let mean = total / sorted.len() as u32;
+    let p99 = sorted[sorted.len() * 99 / 100];
+    let max = sorted[sorted.len() - 1];
+    println!(
+        "{} ships, pooling {}: mean {:?}, p99 {:?}, max {:?} over {} frames",
+        SHIPS,
+        if pooled { "on" } else { "off" },
+        mean,
+        p99,
+        max,
+        sorted.len()
+    );
+}
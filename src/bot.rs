@@ -0,0 +1,405 @@
+//! Computer pilots that fill empty player slots.
+//!
+//! Bots only decide which buttons to press, in the same format as the network
+//! input. In a networked session the input goes through ggrs like a human's
+//! so every peer sees the same thing; without a session it is applied to the
+//! ship directly.
+
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_ggrs::SessionType;
+use bevy_rapier2d::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    components::Team,
+    mode::MatchConfig,
+    orb::Orb,
+    player::Player,
+    ship::{Energy, Ship, ShipInput},
+    utils,
+    weapon::{Projectile, ProjectileLifetime, ProjectileSpeed, Weapon, WeaponGroup},
+    GameState, INPUT_FIRE, INPUT_LEFT, INPUT_RIGHT, INPUT_UP,
+};
+
+mod personality;
+pub use personality::{BotPreset, Personality};
+
+/// Projectiles passing closer than this are dodged.
+const DODGE_DISTANCE: f32 = 80.0;
+/// How far ahead in seconds the most careful bots look for incoming
+/// projectiles.
+const DODGE_TIME: f32 = 0.6;
+/// Bots stop thrusting when they are this close to their target, depending
+/// on their aggression.
+const ENGAGE_DISTANCE_CAREFUL: f32 = 600.0;
+const ENGAGE_DISTANCE_AGGRESSIVE: f32 = 120.0;
+/// Frames a bot keeps the same aim error.
+const AIM_ERROR_FRAMES: u32 = 30;
+/// Largest angle in radians between heading and aim point to open fire.
+const FIRE_ANGLE: f32 = 0.15;
+/// Used to lead targets before the weapon definitions are loaded.
+const DEFAULT_PROJECTILE_SPEED: f32 = 500.0;
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BotInputs>()
+            .init_resource::<BotSettings>()
+            .add_system_set(SystemSet::on_update(GameState::Lobby).with_system(choose_presets))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(think)
+                    .with_system(apply_local.after(think)),
+            );
+    }
+}
+
+/// Player flown by a bot.
+#[derive(Component)]
+pub struct Bot {
+    personality: Personality,
+    /// Seeded so the same bot makes the same mistakes every match.
+    rng: StdRng,
+    /// Decisions waiting for the reaction delay to pass.
+    pending: VecDeque<u8>,
+    aim_error: f32,
+    frame: u32,
+}
+
+impl Bot {
+    pub fn new(preset: BotPreset, seed: u64) -> Self {
+        Self {
+            personality: preset.personality(),
+            rng: StdRng::seed_from_u64(seed),
+            pending: VecDeque::new(),
+            aim_error: 0.0,
+            frame: 0,
+        }
+    }
+
+    /// Draws a new aim error every [`AIM_ERROR_FRAMES`] frames.
+    fn update_aim_error(&mut self) {
+        self.frame += 1;
+        let max = self.personality.aim_error;
+        if self.frame % AIM_ERROR_FRAMES == 1 && max > 0.0 {
+            self.aim_error = self.rng.gen_range(-max..=max);
+        }
+    }
+
+    /// Delays `buttons` by the reaction time, nothing is pressed until the
+    /// first decision comes through.
+    fn react(&mut self, buttons: u8) -> u8 {
+        self.pending.push_back(buttons);
+        if self.pending.len() > self.personality.reaction_frames {
+            self.pending.pop_front().unwrap_or_default()
+        } else {
+            0
+        }
+    }
+}
+
+/// Presets of the bot slots, in slot order, and the seed of their mistakes.
+#[derive(Default)]
+pub struct BotSettings {
+    pub presets: Vec<BotPreset>,
+    pub seed: u64,
+    /// Bot slot changed in the lobby.
+    pub selected: usize,
+    /// Set when another peer flies the bots, the presets can only be changed
+    /// there.
+    pub remote: bool,
+}
+
+impl BotSettings {
+    /// Slots without a preset of their own use the last one given.
+    pub fn preset(&self, slot: usize) -> BotPreset {
+        self.presets
+            .get(slot)
+            .or_else(|| self.presets.last())
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// The latest input of every bot, by player handle.
+#[derive(Default)]
+pub struct BotInputs(pub HashMap<usize, u8>);
+
+/// What a bot knows about a ship.
+#[derive(Clone, Copy)]
+struct Body {
+    position: Vec2,
+    velocity: Vec2,
+}
+
+/// Buttons turning `transform` towards `point` and the remaining angle.
+pub fn turn_towards(transform: &Transform, point: Vec2, tolerance: f32) -> (u8, f32) {
+    let (dx, dy) = utils::direction(transform);
+    let angle = Vec2::new(dx, dy).angle_between(point - transform.translation.truncate());
+    let buttons = if angle > tolerance {
+        INPUT_LEFT
+    } else if angle < -tolerance {
+        INPUT_RIGHT
+    } else {
+        0
+    };
+    (buttons, angle)
+}
+
+/// Where to aim so a projectile with `speed` hits a target keeping its
+/// velocity.
+fn lead(shooter: Vec2, target: Body, speed: f32) -> Vec2 {
+    if speed <= 0.0 {
+        return target.position;
+    }
+    let mut aim = target.position;
+    // A few refinements are plenty at our speeds.
+    for _ in 0..3 {
+        let time = aim.distance(shooter) / speed;
+        aim = target.position + target.velocity * time;
+    }
+    aim
+}
+
+/// The point to flee to from the most dangerous projectile about to pass
+/// within `DODGE_DISTANCE` in the next `look_ahead` seconds.
+fn dodge(me: Body, projectiles: &[Body], look_ahead: f32) -> Option<Vec2> {
+    projectiles
+        .iter()
+        .filter_map(|p| {
+            let offset = me.position - p.position;
+            let relative = p.velocity - me.velocity;
+            let speed = relative.length_squared();
+            if speed == 0.0 {
+                return None;
+            }
+            let time = offset.dot(relative) / speed;
+            if !(0.0..look_ahead).contains(&time) {
+                return None;
+            }
+            let miss = offset - relative * time;
+            (miss.length() < DODGE_DISTANCE).then(|| (time, p, miss))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, p, miss)| {
+            // Move sideways out of the projectile's path.
+            let side = if miss == Vec2::ZERO {
+                p.velocity.perp()
+            } else {
+                miss
+            };
+            me.position + side.normalize_or_zero() * DODGE_DISTANCE * 2.0
+        })
+}
+
+fn closest(from: Vec2, points: impl Iterator<Item = Vec2>) -> Option<Vec2> {
+    points.min_by(|a, b| {
+        a.distance_squared(from)
+            .total_cmp(&b.distance_squared(from))
+    })
+}
+
+#[allow(clippy::type_complexity)]
+fn think(
+    mut inputs: ResMut<BotInputs>,
+    mut bots: Query<(
+        &mut Bot,
+        &Player,
+        &Team,
+        &Transform,
+        Option<&Velocity>,
+        Option<&Energy>,
+        Option<&Children>,
+    )>,
+    ships: Query<(&Team, &Transform, Option<&Velocity>), With<Ship>>,
+    orbs: Query<&Transform, With<Orb>>,
+    projectiles: Query<(&Team, &Transform, &Velocity), With<Projectile>>,
+    weapons: Query<(&WeaponGroup, &ProjectileSpeed, &ProjectileLifetime), With<Weapon>>,
+) {
+    let body = |transform: &Transform, velocity: Option<&Velocity>| Body {
+        position: transform.translation.truncate(),
+        velocity: velocity.map_or(Vec2::ZERO, |v| v.linvel),
+    };
+
+    for (mut bot, player, team, transform, velocity, energy, children) in bots.iter_mut() {
+        let personality = bot.personality;
+        bot.update_aim_error();
+
+        let me = body(transform, velocity);
+        let (speed, range) = children
+            .iter()
+            .flat_map(|c| c.iter())
+            .filter_map(|c| weapons.get(*c).ok())
+            .find(|(group, _, _)| **group == WeaponGroup::Primary)
+            .map_or(
+                (DEFAULT_PROJECTILE_SPEED, f32::MAX),
+                |(_, speed, lifetime)| (speed.0, speed.0 * lifetime.0),
+            );
+
+        let incoming: Vec<Body> = projectiles
+            .iter()
+            .filter(|(t, _, _)| *t != team)
+            .map(|(_, transform, velocity)| body(transform, Some(velocity)))
+            .collect();
+        let low_energy = energy.map_or(false, |e| e.current < e.max * personality.orb_greed);
+        // Aggressive bots only dodge what is about to hit them.
+        let look_ahead = DODGE_TIME * (1.0 - 0.8 * personality.aggression);
+        let engage_distance = ENGAGE_DISTANCE_CAREFUL
+            + (ENGAGE_DISTANCE_AGGRESSIVE - ENGAGE_DISTANCE_CAREFUL) * personality.aggression;
+        let target = ships
+            .iter()
+            .filter(|(t, _, _)| *t != team)
+            .map(|(_, transform, velocity)| body(transform, velocity))
+            .min_by(|a, b| {
+                let a = a.position.distance_squared(me.position);
+                a.total_cmp(&b.position.distance_squared(me.position))
+            });
+
+        let mut buttons = 0;
+        if let Some(escape) = dodge(me, &incoming, look_ahead) {
+            let (turn, angle) = turn_towards(transform, escape, FIRE_ANGLE);
+            buttons |= turn;
+            if angle.abs() < std::f32::consts::FRAC_PI_2 {
+                buttons |= INPUT_UP;
+            }
+        } else if let Some(orb) =
+            closest(me.position, orbs.iter().map(|t| t.translation.truncate()))
+                .filter(|_| low_energy)
+        {
+            let (turn, _) = turn_towards(transform, orb, FIRE_ANGLE);
+            buttons |= turn | INPUT_UP;
+        } else if let Some(target) = target {
+            let aim = lead(me.position, target, speed);
+            let aim = me.position + Vec2::from_angle(bot.aim_error).rotate(aim - me.position);
+            let (turn, angle) = turn_towards(transform, aim, FIRE_ANGLE / 2.0);
+            let distance = aim.distance(me.position);
+            buttons |= turn;
+            if distance > engage_distance {
+                buttons |= INPUT_UP;
+            }
+            if angle.abs() < FIRE_ANGLE && distance < range {
+                buttons |= INPUT_FIRE;
+            }
+        }
+        inputs.0.insert(player.handle, bot.react(buttons));
+    }
+}
+
+/// Lets the players pick the preset of each bot slot while waiting: Tab
+/// selects the next slot, left and right change its preset.
+fn choose_presets(
+    keys: Res<Input<KeyCode>>,
+    config: Res<MatchConfig>,
+    mut settings: ResMut<BotSettings>,
+) {
+    let bots = config.players() - config.humans();
+    if bots == 0 || settings.remote {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        settings.selected = (settings.selected + 1) % bots;
+    }
+    if keys.any_just_pressed([KeyCode::Left, KeyCode::Right]) {
+        let selected = settings.selected.min(bots - 1);
+        let presets: Vec<BotPreset> = (0..bots).map(|slot| settings.preset(slot)).collect();
+        settings.presets = presets;
+        let preset = &mut settings.presets[selected];
+        *preset = if keys.just_pressed(KeyCode::Right) {
+            preset.next()
+        } else {
+            preset.previous()
+        };
+    }
+}
+
+/// Flies the bots' ships directly when there is no network session.
+fn apply_local(
+    session: Option<Res<SessionType>>,
+    inputs: Res<BotInputs>,
+    mut bots: Query<(&Player, &mut ShipInput), With<Bot>>,
+) {
+    if session.is_some() {
+        return;
+    }
+    for (player, mut input) in bots.iter_mut() {
+        if let Some(buttons) = inputs.0.get(&player.handle) {
+            input.0 = *buttons;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(position: Vec2, velocity: Vec2) -> Body {
+        Body { position, velocity }
+    }
+
+    #[test]
+    fn lead_aims_ahead_of_moving_targets() {
+        let target = body(Vec2::new(500.0, 0.0), Vec2::new(0.0, 100.0));
+        let aim = lead(Vec2::ZERO, target, 500.0);
+        assert!(aim.y > 90.0 && aim.y < 110.0, "{:?}", aim);
+
+        let still = body(Vec2::new(500.0, 0.0), Vec2::ZERO);
+        assert_eq!(lead(Vec2::ZERO, still, 500.0), still.position);
+        assert_eq!(lead(Vec2::ZERO, target, 0.0), target.position);
+    }
+
+    #[test]
+    fn dodge_flees_sideways_from_projectiles_on_course() {
+        let me = body(Vec2::ZERO, Vec2::ZERO);
+        let incoming = body(Vec2::new(0.0, -100.0), Vec2::new(0.0, 500.0));
+        let escape = dodge(me, &[incoming], 0.6).expect("projectile on course");
+        assert_eq!(escape.y, 0.0);
+        assert!(escape.x.abs() > DODGE_DISTANCE);
+    }
+
+    #[test]
+    fn dodge_ignores_projectiles_that_miss_or_fly_away() {
+        let me = body(Vec2::ZERO, Vec2::ZERO);
+        let missing = body(Vec2::new(300.0, -100.0), Vec2::new(0.0, 500.0));
+        let leaving = body(Vec2::new(0.0, 100.0), Vec2::new(0.0, 500.0));
+        let too_far = body(Vec2::new(0.0, -1000.0), Vec2::new(0.0, 500.0));
+        assert_eq!(dodge(me, &[missing, leaving, too_far], 0.6), None);
+    }
+
+    #[test]
+    fn reactions_are_delayed_by_the_preset() {
+        let mut bot = Bot::new(BotPreset::Hard, 0);
+        let delay = BotPreset::Hard.personality().reaction_frames;
+        let pressed: Vec<u8> = (1..=delay as u8 + 2).map(|b| bot.react(b)).collect();
+        assert!(pressed[..delay].iter().all(|b| *b == 0));
+        assert_eq!(pressed[delay..], [1, 2]);
+    }
+
+    #[test]
+    fn slots_without_a_preset_use_the_last_one() {
+        let settings = BotSettings {
+            presets: vec![BotPreset::Easy, BotPreset::Sniper],
+            ..Default::default()
+        };
+        assert_eq!(settings.preset(0), BotPreset::Easy);
+        assert_eq!(settings.preset(5), BotPreset::Sniper);
+        assert_eq!(BotSettings::default().preset(0), BotPreset::Normal);
+    }
+
+    #[test]
+    fn bots_with_the_same_seed_make_the_same_mistakes() {
+        let errors = |seed| {
+            let mut bot = Bot::new(BotPreset::Easy, seed);
+            (0..AIM_ERROR_FRAMES * 5)
+                .map(|_| {
+                    bot.update_aim_error();
+                    bot.aim_error
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(errors(7), errors(7));
+        assert_ne!(errors(7), errors(8));
+    }
+}
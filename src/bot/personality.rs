@@ -0,0 +1,127 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Named sets of bot parameters.
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BotPreset {
+    Easy,
+    Normal,
+    Hard,
+    /// Keeps its distance and rarely misses.
+    Sniper,
+    /// Charges in and sprays, ignoring most incoming fire.
+    Brawler,
+}
+
+impl BotPreset {
+    pub const ALL: [BotPreset; 5] = [
+        BotPreset::Easy,
+        BotPreset::Normal,
+        BotPreset::Hard,
+        BotPreset::Sniper,
+        BotPreset::Brawler,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BotPreset::Easy => "Easy",
+            BotPreset::Normal => "Normal",
+            BotPreset::Hard => "Hard",
+            BotPreset::Sniper => "Sniper",
+            BotPreset::Brawler => "Brawler",
+        }
+    }
+
+    /// The preset after this one, wrapping around.
+    pub fn next(&self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// The preset before this one, wrapping around.
+    pub fn previous(&self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|p| p == self).unwrap_or(0)
+    }
+
+    pub fn personality(&self) -> Personality {
+        match self {
+            BotPreset::Easy => Personality {
+                reaction_frames: 20,
+                aim_error: 0.35,
+                aggression: 0.3,
+                orb_greed: 0.2,
+            },
+            BotPreset::Normal => Personality {
+                reaction_frames: 10,
+                aim_error: 0.15,
+                aggression: 0.5,
+                orb_greed: 0.3,
+            },
+            BotPreset::Hard => Personality {
+                reaction_frames: 4,
+                aim_error: 0.05,
+                aggression: 0.7,
+                orb_greed: 0.4,
+            },
+            BotPreset::Sniper => Personality {
+                reaction_frames: 8,
+                aim_error: 0.02,
+                aggression: 0.1,
+                orb_greed: 0.3,
+            },
+            BotPreset::Brawler => Personality {
+                reaction_frames: 6,
+                aim_error: 0.25,
+                aggression: 1.0,
+                orb_greed: 0.15,
+            },
+        }
+    }
+}
+
+impl Default for BotPreset {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// How a bot plays.
+#[derive(Clone, Copy, Debug)]
+pub struct Personality {
+    /// Frames between seeing something and reacting to it.
+    pub reaction_frames: usize,
+    /// Largest error in radians added to the aim.
+    pub aim_error: f32,
+    /// From 0, keeping its distance and dodging everything, to 1, closing in
+    /// and ignoring incoming fire.
+    pub aggression: f32,
+    /// Share of its energy below which the bot goes for orbs.
+    pub orb_greed: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_cycle_in_both_directions() {
+        assert_eq!(BotPreset::Easy.previous(), BotPreset::Brawler);
+        assert_eq!(BotPreset::Brawler.next(), BotPreset::Easy);
+        for preset in BotPreset::ALL {
+            assert_eq!(preset.next().previous(), preset);
+        }
+    }
+
+    #[test]
+    fn harder_presets_react_faster_and_miss_less() {
+        let [easy, normal, hard] =
+            [BotPreset::Easy, BotPreset::Normal, BotPreset::Hard].map(|p| p.personality());
+        assert!(easy.reaction_frames > normal.reaction_frames);
+        assert!(normal.reaction_frames > hard.reaction_frames);
+        assert!(easy.aim_error > normal.aim_error && normal.aim_error > hard.aim_error);
+    }
+}
@@ -1,6 +1,43 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Color, Component};
 
 #[derive(Component)]
 pub struct Hp(pub f32);
 
+#[derive(Component)]
+pub struct MaxHp(pub f32);
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Team(pub usize);
+
+impl Team {
+    pub fn name(&self) -> &'static str {
+        match self.0 % 4 {
+            0 => "Blue team",
+            1 => "Red team",
+            2 => "Green team",
+            _ => "Yellow team",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self.0 % 4 {
+            0 => Color::rgb(0.3, 0.55, 1.0),
+            1 => Color::rgb(1.0, 0.35, 0.3),
+            2 => Color::rgb(0.35, 0.9, 0.35),
+            _ => Color::rgb(1.0, 0.85, 0.2),
+        }
+    }
+}
+
+/// Damage dealt and ships destroyed by a ship's weapons.
+#[derive(Component, Default, Debug)]
+pub struct Score {
+    pub damage: f32,
+    pub kills: u32,
+}
+
+/// Despawned when a round ends: ships, orbs, projectiles and their effects.
+#[derive(Component)]
+pub struct RoundEntity;
+
 mod orb;
@@ -0,0 +1,177 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::Serialize;
+
+use crate::{orb::OrbKind, player::Player, GameState};
+
+pub struct ShipDestroyed {
+    pub ship: Entity,
+    pub name: String,
+    pub killer: Option<Entity>,
+    pub killer_name: Option<String>,
+}
+
+pub struct OrbCollected {
+    pub ship: Entity,
+    pub kind: OrbKind,
+}
+
+pub struct DamageDealt {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub amount: f32,
+}
+
+pub struct PlayerJoined {
+    pub handle: usize,
+}
+
+pub struct PlayerLeft {
+    pub handle: usize,
+}
+
+pub struct GameEventsPlugin;
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShipDestroyed>()
+            .add_event::<OrbCollected>()
+            .add_event::<DamageDealt>()
+            .add_event::<PlayerJoined>()
+            .add_event::<PlayerLeft>()
+            .add_system(player_presence)
+            .add_system_to_stage(CoreStage::Last, write_log);
+    }
+}
+
+/// Players join with their first ship of a match and leave when the match
+/// is left for the main menu, not every time their ship is destroyed.
+fn player_presence(
+    players: Query<&Player, Added<Player>>,
+    state: Res<State<GameState>>,
+    mut handles: Local<HashSet<usize>>,
+    mut joined: EventWriter<PlayerJoined>,
+    mut left: EventWriter<PlayerLeft>,
+) {
+    if state.is_changed() && *state.current() == GameState::MainMenu {
+        for handle in handles.drain() {
+            left.send(PlayerLeft { handle });
+        }
+    }
+    for player in players.iter() {
+        if handles.insert(player.handle) {
+            joined.send(PlayerJoined {
+                handle: player.handle,
+            });
+        }
+    }
+}
+
+/// One line of the match event log.
+#[derive(Serialize)]
+struct LogLine<'a> {
+    time: f64,
+    #[serde(flatten)]
+    entry: LogEntry<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event")]
+enum LogEntry<'a> {
+    ShipDestroyed {
+        ship: &'a str,
+        killer: Option<&'a str>,
+    },
+    OrbCollected {
+        ship: u32,
+        kind: String,
+    },
+    DamageDealt {
+        target: u32,
+        source: Option<u32>,
+        amount: f32,
+    },
+    PlayerJoined {
+        handle: usize,
+    },
+    PlayerLeft {
+        handle: usize,
+    },
+}
+
+/// Writes all gameplay events as JSON lines for post-match analysis.
+pub struct EventLog(BufWriter<File>);
+
+impl EventLog {
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Self(BufWriter::new(File::create(path)?)))
+    }
+
+    fn write(&mut self, time: f64, entry: LogEntry) {
+        let line = LogLine { time, entry };
+        let result = serde_json::to_writer(&mut self.0, &line)
+            .map_err(std::io::Error::from)
+            .and_then(|_| self.0.write_all(b"\n"));
+        if let Err(e) = result {
+            warn!("failed to write event log: {}", e);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_log(
+    log: Option<ResMut<EventLog>>,
+    time: Res<Time>,
+    mut destroyed: EventReader<ShipDestroyed>,
+    mut collected: EventReader<OrbCollected>,
+    mut damage: EventReader<DamageDealt>,
+    mut joined: EventReader<PlayerJoined>,
+    mut left: EventReader<PlayerLeft>,
+) {
+    let mut log = match log {
+        Some(log) => log,
+        None => return,
+    };
+    let t = time.seconds_since_startup();
+
+    for e in destroyed.iter() {
+        log.write(
+            t,
+            LogEntry::ShipDestroyed {
+                ship: &e.name,
+                killer: e.killer_name.as_deref(),
+            },
+        );
+    }
+    for e in collected.iter() {
+        log.write(
+            t,
+            LogEntry::OrbCollected {
+                ship: e.ship.id(),
+                kind: format!("{:?}", e.kind),
+            },
+        );
+    }
+    for e in damage.iter() {
+        log.write(
+            t,
+            LogEntry::DamageDealt {
+                target: e.target.id(),
+                source: e.source.map(|s| s.id()),
+                amount: e.amount,
+            },
+        );
+    }
+    for e in joined.iter() {
+        log.write(t, LogEntry::PlayerJoined { handle: e.handle });
+    }
+    for e in left.iter() {
+        log.write(t, LogEntry::PlayerLeft { handle: e.handle });
+    }
+    if let Err(e) = log.0.flush() {
+        warn!("failed to flush event log: {}", e);
+    }
+}
@@ -1,61 +1,77 @@
 use bevy::prelude::*;
 use bevy::sprite::Anchor;
-use bevy::window::WindowResized;
+use bevy::transform::TransformSystem;
 
-use crate::player::Player;
-use crate::ship::Energy;
+mod announcement;
+mod hit;
+mod kill_feed;
+mod layout;
+mod panel;
+mod radar;
+mod screens;
+mod timer;
+mod wave;
+
+pub use announcement::{Announcements, Countdown};
+pub use layout::HudSettings;
+pub use timer::MatchTimer;
+
+use self::layout::{HudAnchor, ScreenAnchor};
 
 pub struct HudPlugin;
 
-#[derive(Default, Debug)]
-struct EnergyBarPosition {
-    x: f32,
-    y: f32,
+/// Fonts bundled in `assets/fonts`.
+pub struct HudFonts {
+    pub bold: Handle<Font>,
+    pub mono: Handle<Font>,
 }
 
-#[derive(Component)]
-struct EnergyBar;
-
-#[derive(Component)]
-struct EnergyBarBg;
+impl FromWorld for HudFonts {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            bold: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            mono: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        }
+    }
+}
 
-const OUTER_WIDTH: f32 = 400.0;
-const OUTER_HEIGHT: f32 = 30.0;
 const BORDER_SIZE: f32 = 2.0;
 const PADDING: f32 = 20.0;
 const Z_POS: f32 = 900.0;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(EnergyBarPosition::default())
-            .add_startup_system(setup)
-            .add_system(resize_notificator)
-            .add_system(update);
+        app.init_resource::<HudFonts>()
+            .init_resource::<HudSettings>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                layout::layout.before(TransformSystem::TransformPropagate),
+            )
+            .add_plugin(panel::PanelPlugin)
+            .add_plugin(announcement::AnnouncementPlugin)
+            .add_plugin(hit::HitPlugin)
+            .add_plugin(kill_feed::KillFeedPlugin)
+            .add_plugin(radar::RadarPlugin)
+            .add_plugin(screens::ScreensPlugin)
+            .add_plugin(timer::MatchTimerPlugin)
+            .add_plugin(wave::WaveCounterPlugin);
     }
 }
 
-fn calculate_position(w: f32, h: f32) -> (f32, f32) {
-    let x = w / 2.0 - (OUTER_WIDTH + PADDING);
-    let y = h / 2.0 - (OUTER_HEIGHT / 2.0 + PADDING);
-    (x, y)
+/// A bar made of a black border, a dark background and a fill that is scaled
+/// with [`bar_scale`].
+struct Bar {
+    outer: Entity,
+    fill: Entity,
 }
 
-fn setup(
-    mut commands: Commands,
-    windows: Res<Windows>,
-    mut energy_bar_position: ResMut<EnergyBarPosition>,
-) {
-    let w = windows.primary();
-
-    let (x, y) = calculate_position(w.width(), w.height());
-    energy_bar_position.x = x;
-    energy_bar_position.y = y;
-
+fn spawn_bar(commands: &mut Commands, translation: Vec3, size: Vec2, color: Color) -> Bar {
     let outer = commands
         .spawn_bundle(SpriteBundle {
             transform: Transform {
-                translation: Vec3::from((x, y, Z_POS)),
-                scale: Vec3::new(OUTER_WIDTH, OUTER_HEIGHT, 1.0),
+                translation,
+                scale: size.extend(1.0),
                 ..Default::default()
             },
             sprite: Sprite {
@@ -65,16 +81,15 @@ fn setup(
             },
             ..Default::default()
         })
-        .insert(EnergyBarBg)
         .id();
 
     let inner = commands
         .spawn_bundle(SpriteBundle {
             transform: Transform {
-                translation: Vec3::from((BORDER_SIZE / OUTER_WIDTH, 0.0, 1.0)),
+                translation: Vec3::from((BORDER_SIZE / size.x, 0.0, 1.0)),
                 scale: Vec3::new(
-                    1.0 - 2.0 * BORDER_SIZE / OUTER_WIDTH,
-                    1.0 - 2.0 * BORDER_SIZE / OUTER_HEIGHT,
+                    1.0 - 2.0 * BORDER_SIZE / size.x,
+                    1.0 - 2.0 * BORDER_SIZE / size.y,
                     1.0,
                 ),
                 ..Default::default()
@@ -88,17 +103,15 @@ fn setup(
         })
         .id();
 
-    let energy = commands
-        .spawn()
-        .insert(EnergyBar)
-        .insert_bundle(SpriteBundle {
+    let fill = commands
+        .spawn_bundle(SpriteBundle {
             transform: Transform {
-                translation: Vec3::from((BORDER_SIZE / OUTER_WIDTH, 0.0, 2.0)),
+                translation: Vec3::from((BORDER_SIZE / size.x, 0.0, 2.0)),
                 scale: Vec3::splat(0.0),
                 ..Default::default()
             },
             sprite: Sprite {
-                color: Color::BLUE,
+                color,
                 anchor: Anchor::CenterLeft,
                 ..Default::default()
             },
@@ -106,33 +119,15 @@ fn setup(
         })
         .id();
 
-    commands.entity(outer).push_children(&[inner, energy]);
-}
-
-fn update(
-    energy: Query<&Energy, (With<Player>, Changed<Energy>)>,
-    mut energy_bar: Query<&mut Transform, With<EnergyBar>>,
-) {
-    for e in energy.iter() {
-        let mut transform = energy_bar.single_mut();
-        transform.scale = Vec3::new(
-            e.current_percentage() * (1.0 - 2.0 * BORDER_SIZE / OUTER_WIDTH),
-            1.0 - 2.0 * BORDER_SIZE / OUTER_HEIGHT,
-            0.0,
-        );
-    }
+    commands.entity(outer).push_children(&[inner, fill]);
+    Bar { outer, fill }
 }
 
-fn resize_notificator(
-    resize_event: Res<Events<WindowResized>>,
-    mut positions: Query<&mut Transform, With<EnergyBarBg>>,
-) {
-    let mut reader = resize_event.get_reader();
-
-    for e in reader.iter(&resize_event) {
-        let (x, y) = calculate_position(e.width, e.height);
-        for mut pos in positions.iter_mut() {
-            pos.translation = Vec3::from((x, y, Z_POS));
-        }
-    }
+/// Scale of a bar's fill showing `percentage`, relative to the bar's size.
+fn bar_scale(percentage: f32, size: Vec2) -> Vec3 {
+    Vec3::new(
+        percentage.clamp(0.0, 1.0) * (1.0 - 2.0 * BORDER_SIZE / size.x),
+        1.0 - 2.0 * BORDER_SIZE / size.y,
+        0.0,
+    )
 }
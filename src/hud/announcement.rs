@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{HudAnchor, HudFonts, ScreenAnchor, Z_POS};
+use crate::{events::ShipDestroyed, mode::MatchConfig, player::Player, round::MatchScores};
+
+const ANNOUNCEMENT_SIZE: f32 = 48.0;
+const COUNTDOWN_SIZE: f32 = 120.0;
+const DEFAULT_DURATION: Duration = Duration::from_secs(2);
+
+pub struct AnnouncementPlugin;
+
+impl Plugin for AnnouncementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Announcements>()
+            .init_resource::<Countdown>()
+            .add_startup_system(setup)
+            .add_system(announce_destroyed)
+            .add_system(announce_match_point)
+            .add_system(show_announcements.after(announce_destroyed))
+            .add_system(show_countdown);
+    }
+}
+
+pub struct Announcement {
+    pub text: String,
+    pub color: Color,
+    pub duration: Duration,
+}
+
+/// Messages shown one after another in the center of the screen,
+/// e.g. "Player 2 destroyed" or "Match point".
+#[derive(Default)]
+pub struct Announcements {
+    queue: VecDeque<Announcement>,
+    current: Option<Timer>,
+}
+
+impl Announcements {
+    pub fn push(&mut self, text: impl Into<String>) {
+        self.push_with(text, Color::WHITE, DEFAULT_DURATION);
+    }
+
+    pub fn push_with(&mut self, text: impl Into<String>, color: Color, duration: Duration) {
+        self.queue.push_back(Announcement {
+            text: text.into(),
+            color,
+            duration,
+        });
+    }
+
+    /// Drops the current and all queued announcements.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.current = None;
+    }
+}
+
+/// Counts down the seconds before a round starts, showing the remaining
+/// seconds in big digits and "GO!" at the end.
+#[derive(Default)]
+pub struct Countdown {
+    timer: Option<Timer>,
+}
+
+impl Countdown {
+    pub fn start(&mut self, seconds: u32) {
+        self.timer = Some(Timer::new(Duration::from_secs(seconds as u64), false));
+    }
+
+    pub fn finished(&self) -> bool {
+        self.timer.as_ref().map_or(true, |t| t.finished())
+    }
+
+    pub fn remaining_secs(&self) -> u32 {
+        self.timer.as_ref().map_or(0, |t| {
+            (t.duration() - t.elapsed()).as_secs_f32().ceil() as u32
+        })
+    }
+}
+
+#[derive(Component)]
+struct AnnouncementText;
+
+#[derive(Component)]
+struct CountdownText;
+
+fn centered_text(fonts: &HudFonts, font_size: f32) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::from_section(
+            "",
+            TextStyle {
+                font: fonts.bold.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+        )
+        .with_alignment(TextAlignment::CENTER),
+        transform: Transform::from_xyz(0.0, 0.0, Z_POS),
+        ..default()
+    }
+}
+
+fn setup(mut commands: Commands, fonts: Res<HudFonts>) {
+    commands
+        .spawn_bundle(centered_text(&fonts, ANNOUNCEMENT_SIZE))
+        .insert(HudAnchor::new(
+            ScreenAnchor::Center,
+            Vec2::new(0.0, -COUNTDOWN_SIZE),
+        ))
+        .insert(AnnouncementText);
+    commands
+        .spawn_bundle(centered_text(&fonts, COUNTDOWN_SIZE))
+        .insert(HudAnchor::new(ScreenAnchor::Center, Vec2::ZERO))
+        .insert(CountdownText);
+}
+
+fn announce_destroyed(
+    mut events: EventReader<ShipDestroyed>,
+    players: Query<&Player>,
+    config: Res<MatchConfig>,
+    mut announcements: ResMut<Announcements>,
+) {
+    for event in events.iter() {
+        // Enemy ships come and go in waves, only players are announced.
+        if let Ok(player) = players.get(event.ship) {
+            announcements.push_with(
+                format!("{} destroyed", event.name),
+                config.player_color(player),
+                DEFAULT_DURATION,
+            );
+        }
+    }
+}
+
+/// Announces once per match when the leader is a point away from the score
+/// limit.
+fn announce_match_point(
+    config: Res<MatchConfig>,
+    scores: Res<MatchScores>,
+    mut announced: Local<bool>,
+    mut announcements: ResMut<Announcements>,
+) {
+    if !scores.is_changed() || !config.mode.scores_kills() || config.score_limit == 0 {
+        return;
+    }
+    let best = scores.ranking().first().map_or(0, |(_, points)| *points);
+    // Scores start over with every match.
+    if best + 1 < config.score_limit {
+        *announced = false;
+    } else if best + 1 == config.score_limit && !*announced {
+        *announced = true;
+        announcements.push_with("Match point", Color::GOLD, DEFAULT_DURATION);
+    }
+}
+
+fn show_announcements(
+    mut announcements: ResMut<Announcements>,
+    mut text: Query<&mut Text, With<AnnouncementText>>,
+    time: Res<Time>,
+) {
+    let mut text = text.single_mut();
+    let announcements = &mut *announcements;
+
+    if let Some(timer) = announcements.current.as_mut() {
+        timer.tick(time.delta());
+        let section = &mut text.sections[0];
+        section.style.color.set_a(timer.percent_left());
+        if !timer.finished() {
+            return;
+        }
+        announcements.current = None;
+        section.value.clear();
+    }
+
+    if let Some(next) = announcements.queue.pop_front() {
+        let section = &mut text.sections[0];
+        section.value = next.text;
+        section.style.color = next.color;
+        announcements.current = Some(Timer::new(next.duration, false));
+    }
+}
+
+fn show_countdown(
+    mut countdown: ResMut<Countdown>,
+    mut text: Query<&mut Text, With<CountdownText>>,
+    mut go: Local<Option<Timer>>,
+    time: Res<Time>,
+) {
+    let mut text = text.single_mut();
+
+    if let Some(timer) = countdown.timer.as_mut() {
+        if !timer.finished() {
+            timer.tick(time.delta());
+            if timer.just_finished() {
+                text.sections[0].value = "GO!".to_string();
+                *go = Some(Timer::from_seconds(1.0, false));
+            } else {
+                text.sections[0].value = countdown.remaining_secs().to_string();
+            }
+        }
+    }
+
+    if let Some(timer) = go.as_mut() {
+        timer.tick(time.delta());
+        if timer.finished() {
+            text.sections[0].value.clear();
+            *go = None;
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::HudFonts;
+use crate::{
+    components::RoundEntity,
+    player::{LocalPlayers, Player},
+    ship,
+    weapon::DamageEvent,
+};
+
+const NUMBER_LIFETIME: Duration = Duration::from_millis(800);
+const NUMBER_RISE_SPEED: f32 = 40.0;
+const INDICATOR_LIFETIME: Duration = Duration::from_millis(600);
+const INDICATOR_RADIUS: f32 = 70.0;
+const INDICATOR_SIZE: Vec2 = Vec2::new(30.0, 6.0);
+
+pub struct HitPlugin;
+
+impl Plugin for HitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_damage_numbers)
+            // Indicators are children of ships that may be despawned this
+            // frame.
+            .add_system(spawn_hit_indicators.before(ship::destroy))
+            .add_system(float_damage_numbers)
+            .add_system(fade_hit_indicators);
+    }
+}
+
+#[derive(Component)]
+struct DamageNumber(Timer);
+
+#[derive(Component)]
+struct HitIndicator(Timer);
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    fonts: Res<HudFonts>,
+) {
+    for event in events.iter() {
+        // Beams deal tiny amounts every frame, those would just be noise.
+        if event.amount < 0.1 {
+            continue;
+        }
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    format!("{:.1}", event.amount),
+                    TextStyle {
+                        font: fonts.bold.clone(),
+                        font_size: 18.0,
+                        color: Color::YELLOW,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                transform: Transform::from_translation(event.position.extend(800.0)),
+                ..default()
+            })
+            .insert(DamageNumber(Timer::new(NUMBER_LIFETIME, false)))
+            .insert(RoundEntity);
+    }
+}
+
+fn float_damage_numbers(
+    mut commands: Commands,
+    mut numbers: Query<(Entity, &mut Transform, &mut Text, &mut DamageNumber)>,
+    time: Res<Time>,
+) {
+    for (e, mut transform, mut text, mut number) in numbers.iter_mut() {
+        number.0.tick(time.delta());
+        if number.0.finished() {
+            commands.entity(e).despawn();
+            continue;
+        }
+        transform.translation.y += NUMBER_RISE_SPEED * time.delta_seconds();
+        text.sections[0].style.color.set_a(number.0.percent_left());
+    }
+}
+
+/// Shows a marker around the local player's ship pointing to where a hit came from.
+fn spawn_hit_indicators(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    players: Query<(&Player, &GlobalTransform)>,
+    sources: Query<&GlobalTransform>,
+    local_players: Option<Res<LocalPlayers>>,
+) {
+    let local_players = match local_players {
+        Some(local_players) => local_players,
+        None => return,
+    };
+    for event in events.iter() {
+        let (player, transform) = match players.get(event.target) {
+            Ok(player) => player,
+            Err(_) => continue,
+        };
+        if !local_players.0.contains(&player.handle) {
+            continue;
+        }
+        let transform = transform.compute_transform();
+        let from = event
+            .source
+            .and_then(|s| sources.get(s).ok())
+            .map_or(event.position, |t| t.translation().truncate());
+        let world_direction = (from - transform.translation.truncate()).normalize_or_zero();
+        if world_direction == Vec2::ZERO {
+            continue;
+        }
+        // The indicator is a child of the ship, so undo the ship's rotation.
+        let direction = (transform.rotation.inverse() * world_direction.extend(0.0)).truncate();
+
+        let indicator = commands
+            .spawn_bundle(SpriteBundle {
+                transform: Transform {
+                    translation: (direction * INDICATOR_RADIUS).extend(10.0),
+                    rotation: Quat::from_rotation_z(direction.y.atan2(direction.x)),
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: Color::RED,
+                    custom_size: Some(INDICATOR_SIZE.yx()),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(HitIndicator(Timer::new(INDICATOR_LIFETIME, false)))
+            .id();
+        commands.entity(event.target).add_child(indicator);
+    }
+}
+
+fn fade_hit_indicators(
+    mut commands: Commands,
+    mut indicators: Query<(Entity, &mut Sprite, &mut HitIndicator)>,
+    time: Res<Time>,
+) {
+    for (e, mut sprite, mut indicator) in indicators.iter_mut() {
+        indicator.0.tick(time.delta());
+        if indicator.0.finished() {
+            commands.entity(e).despawn();
+            continue;
+        }
+        sprite.color.set_a(indicator.0.percent_left());
+    }
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{HudAnchor, HudFonts, ScreenAnchor, PADDING, Z_POS};
+use crate::events::ShipDestroyed;
+
+const MAX_ENTRIES: usize = 5;
+const LINE_HEIGHT: f32 = 22.0;
+const ENTRY_LIFETIME: Duration = Duration::from_secs(6);
+/// Keeps the feed below the player panel in the top right corner.
+const TOP_OFFSET: f32 = 150.0;
+
+pub struct KillFeedPlugin;
+
+impl Plugin for KillFeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup)
+            .add_system(add_entries)
+            .add_system(expire_entries);
+    }
+}
+
+#[derive(Component)]
+struct KillFeed;
+
+#[derive(Component)]
+struct KillFeedEntry(Timer);
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+            0.0, 0.0, Z_POS,
+        )))
+        .insert(HudAnchor::new(
+            ScreenAnchor::TopRight,
+            Vec2::new(-PADDING, -TOP_OFFSET),
+        ))
+        .insert(KillFeed);
+}
+
+fn entry_text(event: &ShipDestroyed) -> String {
+    match &event.killer_name {
+        Some(killer) if event.killer != Some(event.ship) => {
+            format!("{} destroyed {}", killer, event.name)
+        }
+        _ => format!("{} was destroyed", event.name),
+    }
+}
+
+fn add_entries(
+    mut commands: Commands,
+    mut destroyed: EventReader<ShipDestroyed>,
+    feed: Query<(Entity, Option<&Children>), With<KillFeed>>,
+    mut entries: Query<&mut Transform, With<KillFeedEntry>>,
+    fonts: Res<HudFonts>,
+) {
+    let events: Vec<_> = destroyed.iter().collect();
+    if events.is_empty() {
+        return;
+    }
+    let (feed, children) = feed.single();
+    let mut existing: Vec<Entity> = children.map_or(vec![], |c| c.iter().copied().collect());
+
+    // Newest entries go on top, older ones move down and drop out.
+    for &e in existing.iter() {
+        if let Ok(mut transform) = entries.get_mut(e) {
+            transform.translation.y -= LINE_HEIGHT * events.len() as f32;
+        }
+    }
+    while !existing.is_empty() && existing.len() + events.len() > MAX_ENTRIES {
+        commands.entity(existing.remove(0)).despawn_recursive();
+    }
+
+    for (i, event) in events.iter().enumerate() {
+        let y = -LINE_HEIGHT * (events.len() - 1 - i) as f32;
+        let entry = commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    entry_text(event),
+                    TextStyle {
+                        font: fonts.bold.clone(),
+                        font_size: 18.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment {
+                    vertical: VerticalAlign::Top,
+                    horizontal: HorizontalAlign::Right,
+                }),
+                transform: Transform::from_xyz(0.0, y, 0.0),
+                ..default()
+            })
+            .insert(KillFeedEntry(Timer::new(ENTRY_LIFETIME, false)))
+            .id();
+        commands.entity(feed).add_child(entry);
+    }
+}
+
+fn expire_entries(
+    mut commands: Commands,
+    mut entries: Query<(Entity, &mut Text, &mut KillFeedEntry)>,
+    time: Res<Time>,
+) {
+    for (e, mut text, mut entry) in entries.iter_mut() {
+        entry.0.tick(time.delta());
+        if entry.0.finished() {
+            commands.entity(e).despawn_recursive();
+            continue;
+        }
+        text.sections[0]
+            .style
+            .color
+            .set_a(entry.0.percent_left().min(0.25) * 4.0);
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use bevy::window::WindowResized;
+
+/// Point of the window a HUD element is attached to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScreenAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    Center,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl ScreenAnchor {
+    fn position(&self, half: Vec2) -> Vec2 {
+        let (x, y) = match self {
+            ScreenAnchor::TopLeft => (-half.x, half.y),
+            ScreenAnchor::TopCenter => (0.0, half.y),
+            ScreenAnchor::TopRight => (half.x, half.y),
+            ScreenAnchor::Center => (0.0, 0.0),
+            ScreenAnchor::BottomLeft => (-half.x, -half.y),
+            ScreenAnchor::BottomCenter => (0.0, -half.y),
+            ScreenAnchor::BottomRight => (half.x, -half.y),
+        };
+        Vec2::new(x, y)
+    }
+}
+
+/// Places a HUD root entity relative to a window anchor. `offset` is given in
+/// unscaled pixels and scaled together with the element.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct HudAnchor {
+    pub anchor: ScreenAnchor,
+    pub offset: Vec2,
+}
+
+impl HudAnchor {
+    pub fn new(anchor: ScreenAnchor, offset: Vec2) -> Self {
+        Self { anchor, offset }
+    }
+}
+
+/// Scale of the HUD. The HUD is designed for `reference_height` and grows or
+/// shrinks with the window, multiplied by the user's `scale`.
+pub struct HudSettings {
+    pub scale: f32,
+    pub reference_height: f32,
+    pub min_scale: f32,
+    pub max_scale: f32,
+}
+
+impl Default for HudSettings {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            reference_height: 1080.0,
+            min_scale: 0.5,
+            max_scale: 3.0,
+        }
+    }
+}
+
+impl HudSettings {
+    pub fn effective_scale(&self, window_height: f32) -> f32 {
+        let fit = (window_height / self.reference_height).clamp(self.min_scale, self.max_scale);
+        self.scale * fit
+    }
+}
+
+pub fn layout(
+    mut resize_event: EventReader<WindowResized>,
+    windows: Res<Windows>,
+    settings: Res<HudSettings>,
+    mut anchored: Query<(&mut Transform, &HudAnchor)>,
+    added: Query<(), Added<HudAnchor>>,
+) {
+    let resized = resize_event.iter().last().is_some();
+    if !resized && !settings.is_changed() && added.is_empty() {
+        return;
+    }
+    let w = match windows.get_primary() {
+        Some(w) => w,
+        None => return,
+    };
+    let half = Vec2::new(w.width(), w.height()) / 2.0;
+    let scale = settings.effective_scale(w.height());
+
+    for (mut transform, anchor) in anchored.iter_mut() {
+        let position = anchor.anchor.position(half) + anchor.offset * scale;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+}
@@ -0,0 +1,369 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy::text::Text2dBounds;
+
+use super::{bar_scale, spawn_bar, HudAnchor, HudFonts, ScreenAnchor, PADDING, Z_POS};
+use crate::{
+    components::{Hp, MaxHp, Team},
+    mode::{GameMode, MatchConfig},
+    player::Player,
+    round::MatchScores,
+    ship::{Accelerate, Energy},
+    weapon::{Heat, WeaponUpgrades},
+};
+
+const BAR_SIZE: Vec2 = Vec2::new(240.0, 14.0);
+const BAR_SPACING: f32 = 20.0;
+const NAME_HEIGHT: f32 = 28.0;
+const ICON_SIZE: f32 = 12.0;
+const PANEL_WIDTH: f32 = BAR_SIZE.x;
+const PANEL_HEIGHT: f32 = NAME_HEIGHT + 3.0 * BAR_SPACING + ICON_SIZE + 8.0;
+const LOW_ENERGY: f32 = 0.2;
+
+pub struct PanelPlugin;
+
+impl Plugin for PanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(spawn_panels)
+            .add_system(despawn_panels)
+            .add_system(update_bars)
+            .add_system(update_score)
+            .add_system(update_energy_text)
+            .add_system(update_icons);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StatusIcon {
+    Boost,
+    Overheated,
+    Upgraded,
+    LowEnergy,
+}
+
+impl StatusIcon {
+    const ALL: [StatusIcon; 4] = [
+        StatusIcon::Boost,
+        StatusIcon::Overheated,
+        StatusIcon::Upgraded,
+        StatusIcon::LowEnergy,
+    ];
+
+    fn color(&self) -> Color {
+        match self {
+            StatusIcon::Boost => Color::CYAN,
+            StatusIcon::Overheated => Color::RED,
+            StatusIcon::Upgraded => Color::GOLD,
+            StatusIcon::LowEnergy => Color::PURPLE,
+        }
+    }
+}
+
+/// HUD panel showing the state of one player.
+#[derive(Component)]
+struct Panel {
+    player: Entity,
+    energy: Entity,
+    energy_text: Entity,
+    hull: Entity,
+    heat: Entity,
+    score: Entity,
+    icons: Vec<Entity>,
+}
+
+/// Anchor of the panel of player `handle`. Up to four panels go clockwise
+/// around the screen starting at the top left, in bigger matches every team
+/// gets a side of the screen and stacks its panels from the top. Panels are
+/// positioned by their top left corner.
+fn slot_anchor(handle: usize, config: &MatchConfig) -> HudAnchor {
+    let left = PADDING;
+    let right = -PADDING - PANEL_WIDTH;
+    let top = -PADDING;
+    let bottom = PADDING + PANEL_HEIGHT;
+    if config.players() <= 4 {
+        let (anchor, x, y) = match handle {
+            0 => (ScreenAnchor::TopLeft, left, top),
+            1 => (ScreenAnchor::TopRight, right, top),
+            2 => (ScreenAnchor::BottomRight, right, bottom),
+            _ => (ScreenAnchor::BottomLeft, left, bottom),
+        };
+        return HudAnchor::new(anchor, Vec2::new(x, y));
+    }
+
+    // Survival puts everybody on one team, the players split up by handle.
+    let side = |h: usize| match config.mode {
+        GameMode::Survival => h % 2,
+        _ => config.team_of(h).0 % 2,
+    };
+    let row = (0..handle).filter(|h| side(*h) == side(handle)).count();
+    let y = top - row as f32 * (PANEL_HEIGHT + PADDING);
+    match side(handle) {
+        0 => HudAnchor::new(ScreenAnchor::TopLeft, Vec2::new(left, y)),
+        _ => HudAnchor::new(ScreenAnchor::TopRight, Vec2::new(right, y)),
+    }
+}
+
+fn spawn_panels(
+    mut commands: Commands,
+    players: Query<(Entity, &Player), Added<Player>>,
+    config: Res<MatchConfig>,
+    fonts: Res<HudFonts>,
+) {
+    for (e, player) in players.iter() {
+        let text_style = TextStyle {
+            font: fonts.bold.clone(),
+            font_size: 22.0,
+            color: player.color(),
+        };
+        let name = commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(player.name(), text_style.clone()).with_alignment(
+                    TextAlignment {
+                        vertical: VerticalAlign::Top,
+                        horizontal: HorizontalAlign::Left,
+                    },
+                ),
+                text_2d_bounds: Text2dBounds {
+                    size: Vec2::new(PANEL_WIDTH, NAME_HEIGHT),
+                },
+                ..default()
+            })
+            .id();
+        let score = commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    "0",
+                    TextStyle {
+                        font: fonts.mono.clone(),
+                        color: Color::WHITE,
+                        ..text_style
+                    },
+                )
+                .with_alignment(TextAlignment {
+                    vertical: VerticalAlign::Top,
+                    horizontal: HorizontalAlign::Right,
+                }),
+                transform: Transform::from_xyz(PANEL_WIDTH, 0.0, 0.0),
+                ..default()
+            })
+            .id();
+
+        let bar_y = |i: usize| -NAME_HEIGHT - BAR_SPACING * i as f32;
+        let energy = spawn_bar(
+            &mut commands,
+            Vec3::new(0.0, bar_y(0), 0.0),
+            BAR_SIZE,
+            Color::BLUE,
+        );
+        let energy_text = commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: fonts.mono.clone(),
+                        font_size: 12.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Right,
+                }),
+                transform: Transform::from_xyz(PANEL_WIDTH - 4.0, bar_y(0), 3.0),
+                ..default()
+            })
+            .id();
+        let hull = spawn_bar(
+            &mut commands,
+            Vec3::new(0.0, bar_y(1), 0.0),
+            BAR_SIZE,
+            Color::GREEN,
+        );
+        let heat = spawn_bar(
+            &mut commands,
+            Vec3::new(0.0, bar_y(2), 0.0),
+            BAR_SIZE,
+            Color::ORANGE,
+        );
+
+        let icons: Vec<_> = StatusIcon::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, icon)| {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        transform: Transform::from_xyz(
+                            i as f32 * (ICON_SIZE + 4.0),
+                            bar_y(3) + BAR_SIZE.y / 2.0,
+                            0.0,
+                        ),
+                        sprite: Sprite {
+                            color: icon.color(),
+                            custom_size: Some(Vec2::splat(ICON_SIZE)),
+                            anchor: Anchor::TopLeft,
+                            ..default()
+                        },
+                        visibility: Visibility { is_visible: false },
+                        ..default()
+                    })
+                    .id()
+            })
+            .collect();
+
+        commands
+            .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+                0.0, 0.0, Z_POS,
+            )))
+            .insert(slot_anchor(player.handle, &config))
+            .push_children(&[
+                name,
+                score,
+                energy.outer,
+                energy_text,
+                hull.outer,
+                heat.outer,
+            ])
+            .push_children(&icons)
+            .insert(Panel {
+                player: e,
+                energy: energy.fill,
+                energy_text,
+                hull: hull.fill,
+                heat: heat.fill,
+                score,
+                icons,
+            });
+    }
+}
+
+fn despawn_panels(
+    mut commands: Commands,
+    removed: RemovedComponents<Player>,
+    panels: Query<(Entity, &Panel)>,
+) {
+    for player in removed.iter() {
+        for (e, panel) in panels.iter() {
+            if panel.player == player {
+                commands.entity(e).despawn_recursive();
+            }
+        }
+    }
+}
+
+fn update_bars(
+    panels: Query<&Panel>,
+    players: Query<
+        (
+            Option<&Energy>,
+            Option<&Hp>,
+            Option<&MaxHp>,
+            Option<&Children>,
+        ),
+        With<Player>,
+    >,
+    weapons: Query<&Heat>,
+    mut fills: Query<(&mut Transform, &mut Sprite)>,
+) {
+    for panel in panels.iter() {
+        let (energy, hp, max_hp, children) = match players.get(panel.player) {
+            Ok(player) => player,
+            Err(_) => continue,
+        };
+
+        let energy = energy.map_or(0.0, |e| e.current_percentage());
+        let hull = match (hp, max_hp) {
+            (Some(hp), Some(max)) if max.0 > 0.0 => hp.0 / max.0,
+            _ => 0.0,
+        };
+        let hottest = children
+            .into_iter()
+            .flatten()
+            .filter_map(|&c| weapons.get(c).ok())
+            .max_by(|a, b| a.current_percentage().total_cmp(&b.current_percentage()));
+        let heat = hottest.map_or(0.0, |h| h.current_percentage());
+        let overheated = hottest.map_or(false, |h| h.overheated);
+
+        for (fill, percentage) in [
+            (panel.energy, energy),
+            (panel.hull, hull),
+            (panel.heat, heat),
+        ] {
+            if let Ok((mut transform, _)) = fills.get_mut(fill) {
+                transform.scale = bar_scale(percentage, BAR_SIZE);
+            }
+        }
+        if let Ok((_, mut sprite)) = fills.get_mut(panel.heat) {
+            sprite.color = if overheated {
+                Color::RED
+            } else {
+                Color::ORANGE
+            };
+        }
+    }
+}
+
+/// Shows the points of the match, whatever the game mode scores.
+fn update_score(
+    panels: Query<(&Panel, ChangeTrackers<Panel>)>,
+    players: Query<(&Player, Option<&Team>)>,
+    scores: Res<MatchScores>,
+    mut texts: Query<&mut Text>,
+) {
+    for (panel, tracker) in panels.iter() {
+        if !scores.is_changed() && !tracker.is_added() {
+            continue;
+        }
+        if let (Ok((player, team)), Ok(mut text)) =
+            (players.get(panel.player), texts.get_mut(panel.score))
+        {
+            text.sections[0].value = scores.of(&player.name(), team).to_string();
+        }
+    }
+}
+
+fn update_energy_text(
+    panels: Query<&Panel>,
+    energies: Query<&Energy, (With<Player>, Changed<Energy>)>,
+    mut texts: Query<&mut Text>,
+) {
+    for panel in panels.iter() {
+        if let (Ok(energy), Ok(mut text)) =
+            (energies.get(panel.player), texts.get_mut(panel.energy_text))
+        {
+            text.sections[0].value = format!("{:.0}/{:.0}", energy.current(), energy.max);
+        }
+    }
+}
+
+fn update_icons(
+    panels: Query<&Panel>,
+    players: Query<(Option<&Accelerate>, Option<&Energy>, Option<&Children>), With<Player>>,
+    weapons: Query<&Heat>,
+    upgrades: Res<WeaponUpgrades>,
+    mut icons: Query<&mut Visibility>,
+) {
+    for panel in panels.iter() {
+        let (accelerate, energy, children) = match players.get(panel.player) {
+            Ok(player) => player,
+            Err(_) => continue,
+        };
+        let levels = upgrades.get(panel.player);
+
+        for (icon, &e) in StatusIcon::ALL.iter().zip(panel.icons.iter()) {
+            let visible = match icon {
+                StatusIcon::Boost => accelerate.is_some(),
+                StatusIcon::Overheated => children
+                    .into_iter()
+                    .flatten()
+                    .any(|&c| weapons.get(c).map_or(false, |h| h.overheated)),
+                StatusIcon::Upgraded => levels.total() > 0,
+                StatusIcon::LowEnergy => {
+                    energy.map_or(false, |e| e.current_percentage() < LOW_ENERGY)
+                }
+            };
+            if let Ok(mut visibility) = icons.get_mut(e) {
+                visibility.is_visible = visible;
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+
+use super::{HudAnchor, ScreenAnchor, PADDING, Z_POS};
+use crate::{
+    orb::{Orb, OrbKind},
+    player::{LocalPlayers, Player},
+    ship::Ship,
+    weapon::Projectile,
+};
+
+/// World units covered from the center to the edge of the radar.
+const RANGE: f32 = 1500.0;
+const RADIUS: f32 = 90.0;
+const RINGS: usize = 3;
+const RING_WIDTH: f32 = 1.5;
+const SHIP_BLIP: f32 = 6.0;
+const ORB_BLIP: f32 = 4.0;
+const PROJECTILE_BLIP: f32 = 2.0;
+const ENEMY_COLOR: Color = Color::RED;
+
+pub struct RadarPlugin;
+
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup).add_system(update);
+    }
+}
+
+#[derive(Component)]
+struct Radar;
+
+#[derive(Component)]
+struct Blip;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let background = materials.add(ColorMaterial::from(Color::rgba(0.0, 0.1, 0.0, 0.8)));
+    let ring = materials.add(ColorMaterial::from(Color::rgba(0.2, 0.8, 0.2, 0.5)));
+
+    let mut disk = |radius: f32, material: &Handle<ColorMaterial>, z: f32| {
+        commands
+            .spawn_bundle(MaterialMesh2dBundle {
+                mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+                material: material.clone(),
+                transform: Transform::from_xyz(0.0, 0.0, z),
+                ..default()
+            })
+            .id()
+    };
+
+    // Rings are drawn as a ring colored disk covered by a slightly smaller background disk.
+    let mut children = vec![];
+    for i in (1..=RINGS).rev() {
+        let radius = RADIUS * i as f32 / RINGS as f32;
+        let z = (RINGS - i) as f32 * 0.2;
+        children.push(disk(radius, &ring, z));
+        children.push(disk(radius - RING_WIDTH, &background, z + 0.1));
+    }
+
+    commands
+        .spawn_bundle(SpatialBundle::from_transform(Transform::from_xyz(
+            0.0, 0.0, Z_POS,
+        )))
+        .insert(HudAnchor::new(
+            ScreenAnchor::BottomCenter,
+            Vec2::new(0.0, PADDING + RADIUS),
+        ))
+        .insert(Radar)
+        .push_children(&children);
+}
+
+fn update(
+    mut commands: Commands,
+    radar: Query<Entity, With<Radar>>,
+    blips: Query<Entity, With<Blip>>,
+    ships: Query<(Entity, Option<&Player>, &GlobalTransform), Or<(With<Ship>, With<Player>)>>,
+    orbs: Query<(&OrbKind, &GlobalTransform), With<Orb>>,
+    projectiles: Query<(&GlobalTransform, &Visibility), With<Projectile>>,
+    local_players: Option<Res<LocalPlayers>>,
+) {
+    for e in blips.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    let local = ships.iter().find(|(_, player, _)| {
+        player.map_or(false, |p| {
+            local_players
+                .as_ref()
+                .map_or(p.handle == 0, |l| l.0.contains(&p.handle))
+        })
+    });
+    let (local, center) = match local {
+        Some((e, _, transform)) => (e, transform.translation().truncate()),
+        None => return,
+    };
+
+    let to_radar = |position: Vec2, clamp: bool| {
+        let offset = (position - center) / RANGE * RADIUS;
+        if offset.length() <= RADIUS {
+            Some(offset)
+        } else if clamp {
+            Some(offset.clamp_length_max(RADIUS))
+        } else {
+            None
+        }
+    };
+
+    let mut new_blips = vec![];
+    let mut blip = |offset: Vec2, size: f32, color: Color, z: f32| {
+        new_blips.push(
+            commands
+                .spawn_bundle(SpriteBundle {
+                    transform: Transform::from_translation(offset.extend(z)),
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(size)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(Blip)
+                .id(),
+        );
+    };
+
+    for (transform, visibility) in projectiles.iter() {
+        if !visibility.is_visible {
+            // Pooled and currently unused.
+            continue;
+        }
+        if let Some(offset) = to_radar(transform.translation().truncate(), false) {
+            blip(offset, PROJECTILE_BLIP, Color::WHITE, 1.0);
+        }
+    }
+    for (kind, transform) in orbs.iter() {
+        if let Some(offset) = to_radar(transform.translation().truncate(), false) {
+            blip(offset, ORB_BLIP, kind.color(), 2.0);
+        }
+    }
+    for (e, player, transform) in ships.iter() {
+        let color = player.map_or(ENEMY_COLOR, |p| p.color());
+        let offset = if e == local {
+            Some(Vec2::ZERO)
+        } else {
+            to_radar(transform.translation().truncate(), true)
+        };
+        if let Some(offset) = offset {
+            blip(offset, SHIP_BLIP, color, 3.0);
+        }
+    }
+
+    commands.entity(radar.single()).push_children(&new_blips);
+}
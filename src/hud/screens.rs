@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use super::{HudAnchor, HudFonts, ScreenAnchor, Z_POS};
+use crate::{
+    bot::BotSettings,
+    components::Team,
+    mode::MatchConfig,
+    player::Player,
+    round::{MatchOutcome, MatchScores},
+    GameState,
+};
+
+const TITLE_SIZE: f32 = 72.0;
+const TEXT_SIZE: f32 = 32.0;
+
+pub struct ScreensPlugin;
+
+impl Plugin for ScreensPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup)
+            .add_system(show_screens)
+            .add_system_set(SystemSet::on_update(GameState::Lobby).with_system(fill_lobby))
+            .add_system_set(SystemSet::on_enter(GameState::Results).with_system(fill_results));
+    }
+}
+
+/// Text shown full screen while the game is in the given state.
+#[derive(Component)]
+struct Screen(GameState);
+
+#[derive(Component)]
+struct LobbyText;
+
+#[derive(Component)]
+struct ResultsText;
+
+fn screen_text(fonts: &HudFonts, title: &str, text: &str) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::from_sections([
+            TextSection::new(
+                format!("{}\n\n", title),
+                TextStyle {
+                    font: fonts.bold.clone(),
+                    font_size: TITLE_SIZE,
+                    color: Color::WHITE,
+                },
+            ),
+            TextSection::new(
+                text,
+                TextStyle {
+                    font: fonts.mono.clone(),
+                    font_size: TEXT_SIZE,
+                    color: Color::GRAY,
+                },
+            ),
+        ])
+        .with_alignment(TextAlignment::CENTER),
+        transform: Transform::from_xyz(0.0, 0.0, Z_POS),
+        visibility: Visibility { is_visible: false },
+        ..default()
+    }
+}
+
+fn setup(mut commands: Commands, fonts: Res<HudFonts>) {
+    let anchor = HudAnchor::new(ScreenAnchor::Center, Vec2::ZERO);
+    commands
+        .spawn_bundle(screen_text(&fonts, "Bevissimo!", "Press Enter to play"))
+        .insert(anchor)
+        .insert(Screen(GameState::MainMenu));
+    commands
+        .spawn_bundle(screen_text(&fonts, "Lobby", ""))
+        .insert(anchor)
+        .insert(Screen(GameState::Lobby))
+        .insert(LobbyText);
+    commands
+        .spawn_bundle(screen_text(&fonts, "Results", ""))
+        .insert(anchor)
+        .insert(Screen(GameState::Results))
+        .insert(ResultsText);
+}
+
+fn show_screens(state: Res<State<GameState>>, mut screens: Query<(&Screen, &mut Visibility)>) {
+    if !state.is_changed() {
+        return;
+    }
+    for (screen, mut visibility) in screens.iter_mut() {
+        visibility.is_visible = screen.0 == *state.current();
+    }
+}
+
+fn fill_lobby(
+    config: Res<MatchConfig>,
+    bots: Res<BotSettings>,
+    state: Res<State<GameState>>,
+    mut text: Query<&mut Text, With<LobbyText>>,
+) {
+    if !bots.is_changed() && !state.is_changed() && !config.is_changed() {
+        return;
+    }
+    let mut lines = format!("Waiting for {} players...\n", config.humans());
+    if config.can_pick_teams() {
+        lines += "\nPress a player's number to switch teams";
+    } else if config.teams() && config.humans() > 1 {
+        lines += "\nTeams are set with --teams on every peer";
+    }
+    if config.teams() {
+        for team in [Team(0), Team(1)] {
+            let names: Vec<String> = (0..config.players())
+                .filter(|handle| config.team_of(*handle) == team)
+                .map(|handle| Player { handle }.name())
+                .collect();
+            lines += &format!("\n{}: {}", team.name(), names.join(", "));
+        }
+    }
+    if config.bots > 0 {
+        lines += if bots.remote {
+            "\n\nBots (chosen by Player 1)"
+        } else {
+            "\n\nBots (Tab to select, Left/Right to change)"
+        };
+        for handle in (0..config.players()).filter(|h| config.is_bot(*h)) {
+            let slot = handle - config.humans();
+            let marker = if slot == bots.selected && !bots.remote {
+                ">"
+            } else {
+                " "
+            };
+            let name = Player { handle }.name();
+            lines += &format!("\n{} {}: {}", marker, name, bots.preset(slot).name());
+        }
+    }
+    lines += "\n\nPress Enter to start";
+    text.single_mut().sections[1].value = lines;
+}
+
+fn fill_results(
+    scores: Res<MatchScores>,
+    outcome: Res<MatchOutcome>,
+    mut text: Query<&mut Text, With<ResultsText>>,
+) {
+    let mut lines = match (&outcome.summary, &outcome.winner) {
+        (Some(summary), _) => format!("{}\n\n", summary),
+        (None, Some(winner)) => format!("{} wins!\n\n", winner),
+        (None, None) => "Draw\n\n".to_string(),
+    };
+    for (name, points) in scores.ranking() {
+        lines += &format!("{:<12}{:>4}\n", name, points);
+    }
+    lines += "\nPress Enter to continue";
+    text.single_mut().sections[1].value = lines;
+}
@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{HudAnchor, HudFonts, ScreenAnchor, PADDING, Z_POS};
+use crate::GameState;
+
+pub struct MatchTimerPlugin;
+
+impl Plugin for MatchTimerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchTimer>()
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(tick))
+            .add_system(show);
+    }
+}
+
+/// Time of the current match, shown at the top of the screen.
+#[derive(Default)]
+pub struct MatchTimer {
+    pub elapsed: Duration,
+    /// Counts down to zero instead of up when set.
+    pub limit: Option<Duration>,
+}
+
+impl MatchTimer {
+    pub fn remaining(&self) -> Option<Duration> {
+        self.limit.map(|l| l.saturating_sub(self.elapsed))
+    }
+
+    pub fn reset(&mut self, limit: Option<Duration>) {
+        self.elapsed = Duration::ZERO;
+        self.limit = limit;
+    }
+}
+
+#[derive(Component)]
+struct MatchTimerText;
+
+fn setup(mut commands: Commands, fonts: Res<HudFonts>) {
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: fonts.mono.clone(),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment {
+                vertical: VerticalAlign::Top,
+                horizontal: HorizontalAlign::Center,
+            }),
+            transform: Transform::from_xyz(0.0, 0.0, Z_POS),
+            ..default()
+        })
+        .insert(HudAnchor::new(
+            ScreenAnchor::TopCenter,
+            Vec2::new(0.0, -PADDING),
+        ))
+        .insert(MatchTimerText);
+}
+
+fn tick(mut timer: ResMut<MatchTimer>, time: Res<Time>) {
+    timer.elapsed += time.delta();
+}
+
+fn show(timer: Res<MatchTimer>, mut text: Query<&mut Text, With<MatchTimerText>>) {
+    if !timer.is_changed() {
+        return;
+    }
+    let shown = timer.remaining().unwrap_or(timer.elapsed).as_secs();
+    text.single_mut().sections[0].value = format!("{:02}:{:02}", shown / 60, shown % 60);
+}
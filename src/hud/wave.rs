@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+use super::{HudAnchor, HudFonts, ScreenAnchor, PADDING, Z_POS};
+use crate::mode::Wave;
+
+pub struct WaveCounterPlugin;
+
+impl Plugin for WaveCounterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup).add_system(show);
+    }
+}
+
+#[derive(Component)]
+struct WaveText;
+
+fn setup(mut commands: Commands, fonts: Res<HudFonts>) {
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: fonts.bold.clone(),
+                    font_size: 24.0,
+                    color: Color::ORANGE_RED,
+                },
+            )
+            .with_alignment(TextAlignment {
+                vertical: VerticalAlign::Top,
+                horizontal: HorizontalAlign::Center,
+            }),
+            transform: Transform::from_xyz(0.0, 0.0, Z_POS),
+            ..default()
+        })
+        // Below the match timer.
+        .insert(HudAnchor::new(
+            ScreenAnchor::TopCenter,
+            Vec2::new(0.0, -PADDING - 40.0),
+        ))
+        .insert(WaveText);
+}
+
+fn show(wave: Res<Wave>, mut text: Query<&mut Text, With<WaveText>>) {
+    if !wave.is_changed() {
+        return;
+    }
+    let value = &mut text.single_mut().sections[0].value;
+    if wave.number == 0 {
+        value.clear();
+    } else {
+        *value = format!("Wave {}", wave.number);
+    }
+}
@@ -6,17 +6,25 @@ use bevy_ggrs::*;
 use bevy_rapier2d::plugin::RapierConfiguration;
 use bevy_rapier2d::prelude::*;
 use ggrs::InputStatus;
-use player::Player;
+use player::{LocalPlayers, Player};
+use ship::{spawn_ship, FireMode, ShipConfig, ShipInput, WeaponMount};
+use std::f32::consts::TAU;
 use uuid::Uuid;
 use webrtc_socket::peer::{RtcConfig, RtcConfigBuilder};
 use webrtc_socket::{blocking, GgrsSocket, WebRTCSocket};
 
 mod args;
+mod bench;
+mod bot;
 mod components;
+mod events;
 mod hud;
+mod mode;
 mod orb;
 mod player;
+mod round;
 mod ship;
+mod tournament;
 mod utils;
 mod weapon;
 
@@ -29,11 +37,32 @@ struct AnimationState(benimator::State);
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
 enum GameState {
     Setup,
+    MainMenu,
+    Lobby,
+    Countdown,
     Playing,
+    RoundOver,
+    Results,
 }
 
 fn main() {
     let args = args::Args::get();
+    if args.bench_projectiles {
+        bench::run();
+        return;
+    }
+    if let Some(report) = &args.tournament {
+        let bots = bot::BotSettings {
+            presets: args.bot_presets.clone(),
+            seed: args.bot_seed,
+            ..default()
+        };
+        tournament::run(report, args.tournament_matches, bots);
+        return;
+    }
+
+    let match_config = mode::MatchConfig::from_args(&args);
+
     let rtc_config = RtcConfigBuilder::new()
         .address(args.server_address)
         .port(args.port)
@@ -41,6 +70,12 @@ fn main() {
         .password(args.password)
         .build();
 
+    let event_log = args.event_log.as_ref().and_then(|path| {
+        events::EventLog::create(path)
+            .map_err(|e| eprintln!("failed to create event log {:?}: {}", path, e))
+            .ok()
+    });
+
     let mut webrtc_socket = blocking::BlockingWebRTCSocket::connect(rtc_config).unwrap();
 
     let socket = webrtc_socket.ggrs_socket();
@@ -51,11 +86,15 @@ fn main() {
         .with_input_system(input)
         .with_rollback_schedule(Schedule::default().with_stage(
             "ROLLBACK_STAGE",
-            SystemStage::single_threaded().with_system(move_players),
+            SystemStage::single_threaded().with_system(update_ship_inputs),
         ))
         .register_rollback_type::<Transform>()
         .build(&mut app);
 
+    if let Some(event_log) = event_log {
+        app.insert_resource(event_log);
+    }
+
     app.insert_resource(WindowDescriptor {
         title: "Bevissimo!".to_string(),
         present_mode: PresentMode::AutoVsync,
@@ -68,12 +107,29 @@ fn main() {
         ..Default::default()
     })
     .insert_resource(DefaultTaskPoolOptions::with_num_threads(4).create_default_pools())
+    .insert_resource(hud::HudSettings {
+        scale: args.ui_scale,
+        ..default()
+    })
+    .insert_resource(weapon::DamageRules {
+        friendly_fire: match_config.friendly_fire,
+    })
+    .insert_resource(match_config)
+    .insert_resource(bot::BotSettings {
+        presets: args.bot_presets,
+        seed: args.bot_seed,
+        ..default()
+    })
     .add_state(GameState::Setup)
     .add_plugins(DefaultPlugins)
     .add_plugin(AnimationPlugin::default())
     .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
     // .add_plugin(RapierDebugRenderPlugin::default())
     // .add_plugin(player::PlayerPlugin)
+    .add_plugin(events::GameEventsPlugin)
+    .add_plugin(round::RoundPlugin)
+    .add_plugin(mode::ModePlugin)
+    .add_plugin(bot::BotPlugin)
     .add_plugin(hud::HudPlugin)
     .add_plugin(ship::ShipPlugin)
     .add_plugin(ship::energy::EnergyPlugin)
@@ -84,8 +140,10 @@ fn main() {
     .add_startup_system(start_socket.after(setup))
     .add_system(bevy::window::close_on_esc)
     .add_system(animate)
-    .add_system(wait_for_players.exclusive_system())
-    .add_system(spawn_players)
+    .add_system_set(
+        SystemSet::on_update(GameState::Lobby).with_system(wait_for_players.exclusive_system()),
+    )
+    .add_system_set(SystemSet::on_enter(GameState::Countdown).with_system(spawn_ships))
     .run();
 }
 
@@ -98,16 +156,23 @@ fn start_socket(mut commands: Commands) {
     // commands.insert_resource(Some(socket));
 }
 
+/// Distance of the ships from the center at the start of a round.
+const SPAWN_RADIUS: f32 = 300.0;
+
 const INPUT_UP: u8 = 1 << 0;
 const INPUT_DOWN: u8 = 1 << 1;
 const INPUT_LEFT: u8 = 1 << 2;
 const INPUT_RIGHT: u8 = 1 << 3;
 const INPUT_FIRE: u8 = 1 << 4;
+const INPUT_FIRE_SECONDARY: u8 = 1 << 5;
 
 struct GgrsConfig;
 
+/// Players of the running GGRS session.
+struct SessionPlayers(usize);
+
 impl ggrs::Config for GgrsConfig {
-    // 4-directions + fire fits easily in a single byte
+    // 4-directions + two fire buttons fit easily in a single byte
     type Input = u8;
     type State = u8;
     // Matchbox' WebRtcSocket addresses are strings
@@ -115,37 +180,85 @@ impl ggrs::Config for GgrsConfig {
 }
 
 fn wait_for_players(world: &mut World) {
-    let mut socket = world.get_resource_mut::<Option<GgrsSocket>>().unwrap();
-    let socket = socket.as_mut();
-
-    // If there is no socket we've already started the game
-    if socket.is_none() {
+    let config = world.resource::<mode::MatchConfig>();
+    let (num_players, num_humans) = (config.players(), config.humans());
+
+    // If there is no socket the session of an earlier match is still running.
+    // GGRS owns the socket now, so the number of players can't change anymore.
+    if world.resource::<Option<GgrsSocket>>().is_none() {
+        let session_players = world.resource::<SessionPlayers>().0;
+        let mut config = world.resource_mut::<mode::MatchConfig>();
+        if config.players() != session_players {
+            warn!(
+                "Keeping {} players, the session can't be resized",
+                session_players
+            );
+            config.team_size = session_players / 2;
+        }
+    } else if !start_session(world, num_players, num_humans) {
         return;
     }
 
-    // Check for new connections
-    let players = socket.as_ref().unwrap().players();
+    // The lobby stays open to pick teams and bots until confirmed.
+    if round::confirm_pressed(&mut world.resource_mut::<Input<KeyCode>>()) {
+        let _ = world
+            .resource_mut::<State<GameState>>()
+            .set(GameState::Countdown);
+    }
+}
 
-    let num_players = 2;
-    if players.len() < num_players {
-        return; // wait for more players
+/// Starts the GGRS session once all peers have joined, returns whether it did.
+fn start_session(world: &mut World, num_players: usize, num_humans: usize) -> bool {
+    // Check for new connections
+    let players = match world.resource::<Option<GgrsSocket>>() {
+        Some(socket) => socket.players(),
+        None => return false,
+    };
+
+    // The first peer flies the bots, only it may change their presets.
+    let remote_bots = !matches!(players.first(), Some(ggrs::PlayerType::Local));
+    world.resource_mut::<bot::BotSettings>().remote = remote_bots;
+
+    // Extra peers would take slots meant for bots, the match only starts
+    // with exactly the number of humans it is set up for.
+    if players.len() != num_humans {
+        if players.len() > num_humans {
+            warn!(
+                "{} peers joined but only {} players can, waiting for some to leave",
+                players.len(),
+                num_humans
+            );
+        }
+        return false;
     }
 
-    info!("All peers have joined, going in-game");
+    info!("All peers have joined, starting the session");
 
     // create a GGRS P2P session
     let mut session_builder = ggrs::SessionBuilder::<GgrsConfig>::new()
         .with_num_players(num_players)
         .with_input_delay(2);
 
-    for (i, player) in players.into_iter().enumerate() {
+    let mut local_players = LocalPlayers::default();
+    for (i, &player) in players.iter().enumerate() {
+        if let ggrs::PlayerType::Local = player {
+            local_players.0.push(i);
+        }
         session_builder = session_builder
             .add_player(player, i)
             .expect("failed to add player");
     }
 
+    // Bots take the remaining slots and are flown by the first peer, every
+    // other peer receives their input over the network.
+    for i in players.len()..num_players {
+        session_builder = session_builder
+            .add_player(players[0], i)
+            .expect("failed to add bot");
+    }
+
     // move the socket out of the resource (required because GGRS takes ownership of it)
-    let socket = socket.take().unwrap();
+    let socket = world.resource_mut::<Option<GgrsSocket>>().take().unwrap();
 
     // start the GGRS session
     let session = session_builder
@@ -154,9 +267,21 @@ fn wait_for_players(world: &mut World) {
 
     world.insert_non_send_resource(session);
     world.insert_resource(SessionType::P2PSession);
+    world.insert_resource(SessionPlayers(num_players));
+    world.insert_resource(local_players);
+    true
 }
 
-fn input(_: In<ggrs::PlayerHandle>, keys: Res<Input<KeyCode>>) -> u8 {
+fn input(
+    handle: In<ggrs::PlayerHandle>,
+    keys: Res<Input<KeyCode>>,
+    config: Res<mode::MatchConfig>,
+    bot_inputs: Res<bot::BotInputs>,
+) -> u8 {
+    if config.is_bot(handle.0) {
+        return bot_inputs.0.get(&handle.0).copied().unwrap_or_default();
+    }
+
     let mut input = 0u8;
 
     if keys.any_pressed([KeyCode::Up, KeyCode::W]) {
@@ -174,39 +299,22 @@ fn input(_: In<ggrs::PlayerHandle>, keys: Res<Input<KeyCode>>) -> u8 {
     if keys.any_pressed([KeyCode::Space, KeyCode::Return]) {
         input |= INPUT_FIRE;
     }
+    if keys.any_pressed([KeyCode::LShift, KeyCode::E]) {
+        input |= INPUT_FIRE_SECONDARY;
+    }
 
     input
 }
 
-fn move_players(
+/// Hands the confirmed inputs to the ships, which fly themselves from there.
+fn update_ship_inputs(
     inputs: Res<Vec<(u8, InputStatus)>>,
-    mut player_query: Query<(&mut Transform, &Player)>,
+    mut ships: Query<(&Player, &mut ShipInput)>,
 ) {
-    for (mut transform, player) in player_query.iter_mut() {
-        let (input, _) = inputs[player.handle];
-
-        let mut direction = Vec2::ZERO;
-
-        if input & INPUT_UP != 0 {
-            direction.y += 1.;
-        }
-        if input & INPUT_DOWN != 0 {
-            direction.y -= 1.;
-        }
-        if input & INPUT_RIGHT != 0 {
-            direction.x += 1.;
+    for (player, mut ship_input) in ships.iter_mut() {
+        if let Some((input, _)) = inputs.get(player.handle) {
+            ship_input.0 = *input;
         }
-        if input & INPUT_LEFT != 0 {
-            direction.x -= 1.;
-        }
-        if direction == Vec2::ZERO {
-            continue;
-        }
-
-        let move_speed = 0.13;
-        let move_delta = (direction * move_speed).extend(0.);
-
-        transform.translation += move_delta;
     }
 }
 
@@ -220,7 +328,7 @@ fn setup(
 ) {
     rapier_configuration.gravity = Vec2::ZERO;
 
-    let _ = state.overwrite_set(GameState::Playing);
+    let _ = state.overwrite_set(GameState::MainMenu);
 }
 
 fn animate(
@@ -236,30 +344,73 @@ fn animate(
     }
 }
 
-fn spawn_players(mut commands: Commands, mut rip: ResMut<RollbackIdProvider>) {
-    // Player 1
-    commands
-        .spawn_bundle(SpriteBundle {
-            transform: Transform::from_translation(Vec3::new(-2., 0., 0.)),
-            sprite: Sprite {
-                color: Color::rgb(0., 0.47, 1.),
-                ..default()
+fn player_ship_config<'a>(
+    player: &Player,
+    name: &'a str,
+    config: &mode::MatchConfig,
+) -> ShipConfig<'a> {
+    ShipConfig {
+        name,
+        acceleration: 140.0,
+        ship_sprite: "ships/1.png",
+        exhaust_sprite_sheet: "ships/exhaust/exhaust1.png",
+        max_energy: 100.0,
+        energy_decay: 0.8,
+        energy_start_value: 50.0,
+        hull: 20.0,
+        weapons: vec![
+            WeaponMount {
+                definition: "weapons/blaster.weapon.ron",
+                offset: Vec2::ZERO,
+                group: weapon::WeaponGroup::Primary,
             },
-            ..default()
-        })
-        .insert(Player { handle: 0 })
-        .insert(Rollback::new(rip.next_id()));
-
-    // Player 2
-    commands
-        .spawn_bundle(SpriteBundle {
-            transform: Transform::from_translation(Vec3::new(2., 0., 0.)),
-            sprite: Sprite {
-                color: Color::rgb(0., 0.4, 0.),
-                ..Default::default()
+            WeaponMount {
+                definition: "weapons/missile.weapon.ron",
+                offset: Vec2::ZERO,
+                group: weapon::WeaponGroup::Secondary,
             },
-            ..Default::default()
-        })
-        .insert(Player { handle: 1 })
-        .insert(Rollback::new(rip.next_id()));
+        ],
+        fire_mode: FireMode::Together,
+        team: config.team_of(player.handle),
+        tint: config.player_color(player),
+    }
+}
+
+/// Gives every player a new ship at the start of each round, in a circle
+/// facing the center.
+fn spawn_ships(
+    mut commands: Commands,
+    mut rip: ResMut<RollbackIdProvider>,
+    config: Res<mode::MatchConfig>,
+    bot_settings: Res<bot::BotSettings>,
+    asset_server: Res<AssetServer>,
+    mut textures: ResMut<Assets<TextureAtlas>>,
+) {
+    let num_players = config.players();
+    for handle in 0..num_players {
+        let player = Player { handle };
+        let name = player.name();
+        let ship = spawn_ship(
+            player_ship_config(&player, &name, &config),
+            &mut commands,
+            &asset_server,
+            &mut textures,
+        );
+
+        let dir = Vec2::from_angle(TAU * handle as f32 / num_players as f32);
+        let rotation = Quat::from_rotation_z(dir.x.atan2(-dir.y));
+        let mut entity = commands.entity(ship);
+        if config.is_bot(handle) {
+            let preset = bot_settings.preset(handle - config.humans());
+            entity.insert(bot::Bot::new(preset, bot_settings.seed + handle as u64));
+        }
+        entity
+            .insert(
+                Transform::from_translation((dir * SPAWN_RADIUS).extend(0.0))
+                    .with_rotation(rotation),
+            )
+            .insert(ShipInput::default())
+            .insert(player)
+            .insert(Rollback::new(rip.next_id()));
+    }
 }
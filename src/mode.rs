@@ -0,0 +1,265 @@
+//! Rules deciding how a match is won.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::{args::Args, components::Team, player::Player, GameState};
+
+mod deathmatch;
+mod king_of_the_hill;
+mod orb_race;
+mod survival;
+
+pub use survival::{Enemy, Wave};
+
+const DEFAULT_RACE_TIME: Duration = Duration::from_secs(180);
+/// Area in which modes place things every peer has to agree on. Fixed rather
+/// than taken from the window, whose size differs between peers.
+pub const ARENA_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+/// Keys switching the team of the player with the same number in the lobby.
+const TEAM_KEYS: [KeyCode; 6] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+];
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameMode {
+    /// The last ship alive wins the round, most kills after all rounds wins.
+    Elimination,
+    /// Kills score points, ships come back every round until the score or
+    /// time limit is reached.
+    Deathmatch,
+    /// The most orbs collected, or energy banked, when the time runs out wins.
+    OrbRace,
+    /// Holding a capture zone alone scores points every second.
+    KingOfTheHill,
+    /// All players fight waves of enemy ships together until everyone is
+    /// destroyed.
+    Survival,
+}
+
+impl GameMode {
+    /// Whether destroying a ship scores a point.
+    pub fn scores_kills(&self) -> bool {
+        !matches!(self, GameMode::OrbRace | GameMode::KingOfTheHill)
+    }
+
+    /// Whether a round ends once only one team has ships left.
+    pub fn last_team_standing(&self) -> bool {
+        !matches!(self, GameMode::Survival)
+    }
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        Self::Elimination
+    }
+}
+
+pub struct MatchConfig {
+    pub mode: GameMode,
+    /// Rounds in a match, `None` if the mode ends it some other way.
+    pub rounds: Option<u32>,
+    pub score_limit: u32,
+    pub time_limit: Option<Duration>,
+    /// Orb race is won by the energy held at the end instead of orbs collected.
+    pub bank_energy: bool,
+    /// Ships per team, 1 for every ship on its own.
+    pub team_size: usize,
+    pub friendly_fire: bool,
+    /// Player slots filled by bots, taken from the end.
+    pub bots: usize,
+    /// Seed of the [`MatchRng`](crate::round::MatchRng).
+    pub seed: u64,
+    /// Teams given with `--teams` or chosen in the lobby, by player handle.
+    pub team_picks: HashMap<usize, Team>,
+}
+
+impl MatchConfig {
+    pub fn from_args(args: &Args) -> Self {
+        let time_limit = Some(Duration::from_secs(args.time_limit)).filter(|t| !t.is_zero());
+        let teams = Self {
+            team_size: args.team_size.max(1),
+            friendly_fire: args.friendly_fire,
+            bots: args.bots,
+            seed: args.seed,
+            team_picks: args
+                .teams
+                .iter()
+                .enumerate()
+                .filter(|(_, team)| **team < 2)
+                .map(|(handle, team)| (handle, Team(*team)))
+                .collect(),
+            ..Self::default()
+        };
+        match args.mode {
+            GameMode::Elimination => Self {
+                mode: args.mode,
+                rounds: Some(args.rounds),
+                score_limit: 0,
+                time_limit: None,
+                bank_energy: false,
+                ..teams
+            },
+            GameMode::Deathmatch => Self {
+                mode: args.mode,
+                rounds: None,
+                score_limit: args.score_limit,
+                time_limit,
+                bank_energy: false,
+                ..teams
+            },
+            GameMode::OrbRace => Self {
+                mode: args.mode,
+                rounds: None,
+                score_limit: 0,
+                // The race only ends when the time runs out.
+                time_limit: time_limit.or(Some(DEFAULT_RACE_TIME)),
+                bank_energy: args.bank_energy,
+                ..teams
+            },
+            GameMode::KingOfTheHill => Self {
+                mode: args.mode,
+                rounds: None,
+                score_limit: args.hill_score_limit,
+                time_limit,
+                bank_energy: false,
+                ..teams
+            },
+            GameMode::Survival => Self {
+                mode: args.mode,
+                rounds: None,
+                score_limit: 0,
+                time_limit: None,
+                bank_energy: false,
+                ..teams
+            },
+        }
+    }
+
+    pub fn teams(&self) -> bool {
+        self.team_size > 1
+    }
+
+    /// Players needed to start the match.
+    pub fn players(&self) -> usize {
+        self.team_size * 2
+    }
+
+    /// Players that have to join before the match starts, at least one.
+    pub fn humans(&self) -> usize {
+        self.players().saturating_sub(self.bots).max(1)
+    }
+
+    pub fn is_bot(&self, handle: usize) -> bool {
+        handle >= self.humans()
+    }
+
+    /// Players alternate between the two teams unless they picked one, or
+    /// each play on their own. In survival everybody is on the same team.
+    pub fn team_of(&self, handle: usize) -> Team {
+        if self.mode == GameMode::Survival {
+            Team(0)
+        } else if self.teams() {
+            self.team_picks
+                .get(&handle)
+                .copied()
+                .unwrap_or(Team(handle % 2))
+        } else {
+            Team(handle)
+        }
+    }
+
+    /// Whether teams can be picked in the lobby. Picks aren't sent to other
+    /// peers, so only when one human plays against bots. Networked matches
+    /// take their teams from `--teams`, which every peer is started with.
+    pub fn can_pick_teams(&self) -> bool {
+        self.teams() && self.mode != GameMode::Survival && self.humans() == 1
+    }
+
+    /// Team color in team matches, the player's own color otherwise.
+    pub fn player_color(&self, player: &Player) -> Color {
+        if self.teams() {
+            self.team_of(player.handle).color()
+        } else {
+            player.color()
+        }
+    }
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        Self {
+            mode: GameMode::Elimination,
+            rounds: Some(3),
+            score_limit: 0,
+            time_limit: None,
+            bank_energy: false,
+            team_size: 1,
+            friendly_fire: false,
+            bots: 0,
+            seed: 0,
+            team_picks: HashMap::default(),
+        }
+    }
+}
+
+pub struct ModePlugin;
+
+impl Plugin for ModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchConfig>()
+            .add_plugin(deathmatch::DeathmatchPlugin)
+            .add_plugin(orb_race::OrbRacePlugin)
+            .add_plugin(king_of_the_hill::KingOfTheHillPlugin)
+            .add_plugin(survival::SurvivalPlugin)
+            .add_system_set(SystemSet::on_update(GameState::Lobby).with_system(pick_teams));
+    }
+}
+
+/// Moves a player to the other team when their number is pressed in the lobby.
+fn pick_teams(keys: Res<Input<KeyCode>>, mut config: ResMut<MatchConfig>) {
+    if !config.can_pick_teams() {
+        return;
+    }
+    for (handle, key) in TEAM_KEYS.iter().enumerate().take(config.players()) {
+        if keys.just_pressed(*key) {
+            let other = Team(1 - config.team_of(handle).0);
+            config.team_picks.insert(handle, other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn teams_from_args_override_the_alternating_default() {
+        let args = Args::parse_from([
+            "bevissimo",
+            "-u",
+            "t",
+            "-P",
+            "t",
+            "--team-size",
+            "2",
+            "--teams",
+            "0,0,1,7",
+        ]);
+        let config = MatchConfig::from_args(&args);
+        let teams: Vec<Team> = (0..config.players()).map(|h| config.team_of(h)).collect();
+        // The invalid team of the last slot falls back to the default.
+        assert_eq!(teams, [Team(0), Team(0), Team(1), Team(1)]);
+    }
+}
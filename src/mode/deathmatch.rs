@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{GameMode, MatchConfig};
+use crate::{
+    hud::{Announcements, MatchTimer},
+    round::{MatchOutcome, MatchScores},
+    GameState,
+};
+
+pub struct DeathmatchPlugin;
+
+impl Plugin for DeathmatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SuddenDeath>()
+            .add_system_set(SystemSet::on_exit(GameState::Lobby).with_system(reset))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(check_limits));
+    }
+}
+
+/// Set when the time ran out on a tie, the next kill decides the match.
+#[derive(Default)]
+struct SuddenDeath(bool);
+
+fn reset(mut sudden_death: ResMut<SuddenDeath>) {
+    sudden_death.0 = false;
+}
+
+fn check_limits(
+    config: Res<MatchConfig>,
+    scores: Res<MatchScores>,
+    timer: Res<MatchTimer>,
+    mut sudden_death: ResMut<SuddenDeath>,
+    mut outcome: ResMut<MatchOutcome>,
+    mut announcements: ResMut<Announcements>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if config.mode != GameMode::Deathmatch || outcome.decided {
+        return;
+    }
+
+    let best = scores.ranking().first().map_or(0, |(_, points)| *points);
+    let score_reached = config.score_limit > 0 && best >= config.score_limit;
+    let time_up = timer.remaining() == Some(Duration::ZERO);
+
+    if sudden_death.0 {
+        if scores.leader().is_none() {
+            return;
+        }
+    } else if !score_reached && !time_up {
+        return;
+    } else if scores.leader().is_none() {
+        sudden_death.0 = true;
+        announcements.push_with("Sudden death!", Color::RED, Duration::from_secs(3));
+        return;
+    }
+
+    outcome.decide(&scores);
+    let _ = state.set(GameState::RoundOver);
+}
@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+use bevy::utils::HashSet;
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+
+use super::{GameMode, MatchConfig, ARENA_SIZE};
+use crate::{
+    components::{RoundEntity, Team},
+    hud::{Announcements, MatchTimer},
+    player::Player,
+    round::{MatchOutcome, MatchRng, MatchScores},
+    GameState,
+};
+
+const ZONE_RADIUS: f32 = 150.0;
+const RELOCATE_INTERVAL: Duration = Duration::from_secs(30);
+const NEUTRAL_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.15);
+const CONTESTED_COLOR: Color = Color::rgba(1.0, 0.0, 0.0, 0.25);
+
+pub struct KingOfTheHillPlugin;
+
+impl Plugin for KingOfTheHillPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Countdown).with_system(spawn_zone))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(capture)
+                    .with_system(relocate)
+                    .with_system(show_zone.after(capture))
+                    .with_system(finish.after(capture)),
+            );
+    }
+}
+
+/// Area scoring a point per second for the only ship, or team, inside it.
+#[derive(Component)]
+struct CaptureZone {
+    holder: Option<Entity>,
+    contested: bool,
+    points: Timer,
+    relocate: Timer,
+}
+
+fn spawn_zone(
+    mut commands: Commands,
+    config: Res<MatchConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if config.mode != GameMode::KingOfTheHill {
+        return;
+    }
+    commands
+        .spawn_bundle(MaterialMesh2dBundle {
+            mesh: meshes.add(shape::Circle::new(ZONE_RADIUS).into()).into(),
+            material: materials.add(ColorMaterial::from(NEUTRAL_COLOR)),
+            transform: Transform::from_xyz(0.0, 0.0, -1.0),
+            ..default()
+        })
+        .insert(Collider::ball(ZONE_RADIUS))
+        .insert(Sensor)
+        .insert(CaptureZone {
+            holder: None,
+            contested: false,
+            points: Timer::new(Duration::from_secs(1), true),
+            relocate: Timer::new(RELOCATE_INTERVAL, true),
+        })
+        .insert(RoundEntity);
+}
+
+fn capture(
+    config: Res<MatchConfig>,
+    rapier_context: Res<RapierContext>,
+    mut zones: Query<(Entity, &mut CaptureZone)>,
+    ships: Query<(&Name, &Team), With<Player>>,
+    mut scores: ResMut<MatchScores>,
+    time: Res<Time>,
+) {
+    if config.mode != GameMode::KingOfTheHill {
+        return;
+    }
+    for (e, mut zone) in zones.iter_mut() {
+        let inside: Vec<Entity> = rapier_context
+            .intersections_with(e)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(a, b, _)| if a == e { b } else { a })
+            .filter(|other| ships.contains(*other))
+            .collect();
+
+        let teams: HashSet<&Team> = inside
+            .iter()
+            .filter_map(|ship| ships.get(*ship).ok())
+            .map(|(_, team)| team)
+            .collect();
+        // Teammates hold the zone together, the points go to their team.
+        zone.contested = teams.len() > 1;
+        let holder = if zone.contested {
+            None
+        } else if zone.holder.map_or(false, |h| inside.contains(&h)) {
+            zone.holder
+        } else {
+            inside.first().copied()
+        };
+        if holder != zone.holder {
+            zone.holder = holder;
+            zone.points.reset();
+        }
+
+        let holder = match zone.holder {
+            Some(holder) => holder,
+            None => continue,
+        };
+        if zone.points.tick(time.delta()).just_finished() {
+            if let Ok((name, team)) = ships.get(holder) {
+                scores.add(name, Some(team), 1);
+            }
+        }
+    }
+}
+
+/// Moves the zone to a random place on the screen every now and then.
+fn relocate(
+    config: Res<MatchConfig>,
+    mut rng: ResMut<MatchRng>,
+    mut zones: Query<(&mut Transform, &mut CaptureZone)>,
+    mut announcements: ResMut<Announcements>,
+    time: Res<Time>,
+) {
+    if config.mode != GameMode::KingOfTheHill {
+        return;
+    }
+    for (mut transform, mut zone) in zones.iter_mut() {
+        if !zone.relocate.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let range = ARENA_SIZE / 2.0 - ZONE_RADIUS;
+        transform.translation.x = rng.0.gen_range(-range.x..range.x);
+        transform.translation.y = rng.0.gen_range(-range.y..range.y);
+        zone.holder = None;
+        zone.points.reset();
+        announcements.push("The zone moved!");
+    }
+}
+
+fn show_zone(
+    config: Res<MatchConfig>,
+    zones: Query<(&CaptureZone, &Handle<ColorMaterial>), Changed<CaptureZone>>,
+    players: Query<&Player>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (zone, material) in zones.iter() {
+        let color = if zone.contested {
+            CONTESTED_COLOR
+        } else {
+            match zone.holder.and_then(|e| players.get(e).ok()) {
+                Some(player) => *config.player_color(player).set_a(0.3),
+                None => NEUTRAL_COLOR,
+            }
+        };
+        if let Some(material) = materials.get_mut(material) {
+            material.color = color;
+        }
+    }
+}
+
+fn finish(
+    config: Res<MatchConfig>,
+    timer: Res<MatchTimer>,
+    scores: Res<MatchScores>,
+    mut outcome: ResMut<MatchOutcome>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if config.mode != GameMode::KingOfTheHill || outcome.decided {
+        return;
+    }
+    let best = scores.ranking().first().map_or(0, |(_, points)| *points);
+    let score_reached = config.score_limit > 0 && best >= config.score_limit;
+    let time_up = timer.remaining() == Some(Duration::ZERO);
+    if score_reached || time_up {
+        outcome.decide(&scores);
+        let _ = state.set(GameState::RoundOver);
+    }
+}
@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{GameMode, MatchConfig};
+use crate::{
+    components::Team,
+    events::OrbCollected,
+    hud::MatchTimer,
+    orb::OrbSpawnRate,
+    player::Player,
+    round::{MatchOutcome, MatchScores},
+    ship::Energy,
+    GameState,
+};
+
+/// Spawn rate reached when the time runs out.
+const FINAL_SPAWN_RATE: f32 = 4.0;
+
+pub struct OrbRacePlugin;
+
+impl Plugin for OrbRacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_exit(GameState::Lobby).with_system(reset))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(count_orbs)
+                    .with_system(ramp_up)
+                    .with_system(finish.after(count_orbs)),
+            );
+    }
+}
+
+fn reset(mut spawn_rate: ResMut<OrbSpawnRate>) {
+    *spawn_rate = OrbSpawnRate::default();
+}
+
+fn count_orbs(
+    config: Res<MatchConfig>,
+    mut collected: EventReader<OrbCollected>,
+    names: Query<(&Name, Option<&Team>)>,
+    mut scores: ResMut<MatchScores>,
+) {
+    for event in collected.iter() {
+        if config.mode != GameMode::OrbRace || config.bank_energy {
+            continue;
+        }
+        if let Ok((name, team)) = names.get(event.ship) {
+            scores.add(name, team, 1);
+        }
+    }
+}
+
+/// Spawns orbs faster the closer the race gets to the end.
+fn ramp_up(config: Res<MatchConfig>, timer: Res<MatchTimer>, mut spawn_rate: ResMut<OrbSpawnRate>) {
+    if config.mode != GameMode::OrbRace {
+        return;
+    }
+    let limit = match timer.limit {
+        Some(limit) if !limit.is_zero() => limit,
+        _ => return,
+    };
+    let progress = (timer.elapsed.as_secs_f32() / limit.as_secs_f32()).min(1.0);
+    spawn_rate.0 = 1.0 + (FINAL_SPAWN_RATE - 1.0) * progress;
+}
+
+fn finish(
+    config: Res<MatchConfig>,
+    timer: Res<MatchTimer>,
+    ships: Query<(&Name, Option<&Team>, &Energy), With<Player>>,
+    mut scores: ResMut<MatchScores>,
+    mut outcome: ResMut<MatchOutcome>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if config.mode != GameMode::OrbRace
+        || outcome.decided
+        || timer.remaining() != Some(Duration::ZERO)
+    {
+        return;
+    }
+    if config.bank_energy {
+        for (name, team, energy) in ships.iter() {
+            scores.add(name, team, energy.current as u32);
+        }
+    }
+    outcome.decide(&scores);
+    let _ = state.set(GameState::RoundOver);
+}
@@ -0,0 +1,245 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use super::{GameMode, MatchConfig, ARENA_SIZE};
+use crate::{
+    bot,
+    components::Team,
+    events::ShipDestroyed,
+    hud::Announcements,
+    orb::OrbDrop,
+    player::Player,
+    round::{MatchOutcome, MatchRng},
+    ship::{spawn_ship, FireMode, Ship, ShipConfig, ShipInput, WeaponMount},
+    weapon::WeaponGroup,
+    GameState, INPUT_FIRE, INPUT_UP,
+};
+
+const ENEMY_TEAM: Team = Team(1);
+const WAVE_PAUSE: Duration = Duration::from_secs(3);
+const MAX_ENEMIES: u32 = 16;
+const ORB_DROP_CHANCE: f64 = 0.5;
+/// Enemies stop thrusting when they are this close to their target.
+const ENGAGE_DISTANCE: f32 = 300.0;
+const FIRE_DISTANCE: f32 = 700.0;
+const FIRE_ANGLE: f32 = 0.25;
+
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wave>()
+            .add_system_set(SystemSet::on_exit(GameState::Lobby).with_system(reset))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(spawn_waves)
+                    .with_system(steer_enemies)
+                    .with_system(drop_orbs)
+                    .with_system(finish),
+            );
+    }
+}
+
+/// Ship flown by the computer in survival.
+#[derive(Component)]
+pub struct Enemy;
+
+/// The wave of enemies the players are fighting.
+pub struct Wave {
+    pub number: u32,
+    pause: Timer,
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self {
+            number: 0,
+            pause: Timer::new(WAVE_PAUSE, false),
+        }
+    }
+}
+
+fn reset(mut wave: ResMut<Wave>) {
+    *wave = Wave::default();
+}
+
+fn enemy_config(wave: u32, i: u32) -> ShipConfig<'static> {
+    let hull = 10.0 + 2.0 * wave as f32;
+    let acceleration = 100.0 + 5.0 * wave as f32;
+    let tint = Color::rgb(1.0, 0.5, 0.5);
+    let energy = 100.0;
+    // From the third wave on every fourth enemy is a heavier gunship.
+    if wave >= 3 && i % 4 == 3 {
+        ShipConfig {
+            name: "Gunship",
+            acceleration: acceleration * 0.7,
+            ship_sprite: "ships/5B.png",
+            exhaust_sprite_sheet: "ships/exhaust/exhaust1.png",
+            max_energy: energy,
+            energy_decay: 0.0,
+            energy_start_value: energy,
+            hull: hull * 2.5,
+            weapons: vec![
+                WeaponMount {
+                    definition: "weapons/wing_gun.weapon.ron",
+                    offset: Vec2::new(-30.0, 0.0),
+                    group: WeaponGroup::Primary,
+                },
+                WeaponMount {
+                    definition: "weapons/wing_gun.weapon.ron",
+                    offset: Vec2::new(30.0, 0.0),
+                    group: WeaponGroup::Primary,
+                },
+            ],
+            fire_mode: FireMode::Alternate,
+            team: ENEMY_TEAM,
+            tint,
+        }
+    } else {
+        ShipConfig {
+            name: "Drone",
+            acceleration,
+            ship_sprite: "ships/3B.png",
+            exhaust_sprite_sheet: "ships/exhaust/exhaust1.png",
+            max_energy: energy,
+            energy_decay: 0.0,
+            energy_start_value: energy,
+            hull,
+            weapons: vec![WeaponMount {
+                definition: "weapons/blaster.weapon.ron",
+                offset: Vec2::ZERO,
+                group: WeaponGroup::Primary,
+            }],
+            fire_mode: FireMode::Together,
+            team: ENEMY_TEAM,
+            tint,
+        }
+    }
+}
+
+/// Sends the next wave from the edges of the screen once the last one is
+/// destroyed.
+#[allow(clippy::too_many_arguments)]
+fn spawn_waves(
+    mut commands: Commands,
+    config: Res<MatchConfig>,
+    mut wave: ResMut<Wave>,
+    enemies: Query<(), With<Enemy>>,
+    mut rng: ResMut<MatchRng>,
+    asset_server: Res<AssetServer>,
+    mut textures: ResMut<Assets<TextureAtlas>>,
+    mut announcements: ResMut<Announcements>,
+    time: Res<Time>,
+) {
+    if config.mode != GameMode::Survival || !enemies.is_empty() {
+        return;
+    }
+    if !wave.pause.tick(time.delta()).finished() {
+        return;
+    }
+    wave.pause.reset();
+    wave.number += 1;
+    announcements.push(format!("Wave {}", wave.number));
+
+    let half_size = ARENA_SIZE / 2.0;
+    for i in 0..(1 + wave.number).min(MAX_ENEMIES) {
+        // A random point on the arena border, facing the center.
+        let angle = rng.0.gen_range(0.0..TAU);
+        let dir = Vec2::from_angle(angle);
+        let position = dir * (half_size / dir.abs()).min_element();
+        let rotation = Quat::from_rotation_z((dir.x).atan2(-dir.y));
+
+        let ship = spawn_ship(
+            enemy_config(wave.number, i),
+            &mut commands,
+            &asset_server,
+            &mut textures,
+        );
+        commands
+            .entity(ship)
+            .insert(Transform::from_translation(position.extend(0.0)).with_rotation(rotation))
+            .insert(Enemy)
+            .insert(ShipInput::default());
+    }
+}
+
+/// Turns towards the closest player ship, closes in and fires when lined up.
+fn steer_enemies(
+    mut enemies: Query<(&Transform, &mut ShipInput), With<Enemy>>,
+    players: Query<&Transform, (With<Player>, With<Ship>)>,
+) {
+    for (transform, mut input) in enemies.iter_mut() {
+        let position = transform.translation.truncate();
+        let target = players
+            .iter()
+            .map(|t| t.translation.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            });
+
+        let mut buttons = 0;
+        if let Some(target) = target {
+            let (turn, angle) = bot::turn_towards(transform, target, FIRE_ANGLE / 2.0);
+            let distance = target.distance(position);
+
+            buttons |= turn;
+            if distance > ENGAGE_DISTANCE {
+                buttons |= INPUT_UP;
+            }
+            if angle.abs() < FIRE_ANGLE && distance < FIRE_DISTANCE {
+                buttons |= INPUT_FIRE;
+            }
+        }
+        input.0 = buttons;
+    }
+}
+
+fn drop_orbs(
+    config: Res<MatchConfig>,
+    mut destroyed: EventReader<ShipDestroyed>,
+    enemies: Query<&Transform, With<Enemy>>,
+    mut rng: ResMut<MatchRng>,
+    mut drops: EventWriter<OrbDrop>,
+) {
+    if config.mode != GameMode::Survival {
+        return;
+    }
+    for event in destroyed.iter() {
+        if let Ok(transform) = enemies.get(event.ship) {
+            if rng.0.gen_bool(ORB_DROP_CHANCE) {
+                drops.send(OrbDrop {
+                    position: transform.translation.truncate(),
+                });
+            }
+        }
+    }
+}
+
+/// Ends the match once the last player ship is destroyed.
+fn finish(
+    config: Res<MatchConfig>,
+    mut destroyed: EventReader<ShipDestroyed>,
+    players: Query<Entity, (With<Player>, With<Ship>)>,
+    wave: Res<Wave>,
+    mut outcome: ResMut<MatchOutcome>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if config.mode != GameMode::Survival || outcome.decided {
+        return;
+    }
+    let destroyed: Vec<Entity> = destroyed.iter().map(|e| e.ship).collect();
+    if !destroyed.iter().any(|e| players.contains(*e)) {
+        return;
+    }
+    // Destroyed ships are only despawned at the end of the frame.
+    if players.iter().any(|e| !destroyed.contains(&e)) {
+        return;
+    }
+    outcome.decided = true;
+    outcome.summary = Some(format!("Survived {} waves", wave.number.saturating_sub(1)));
+    let _ = state.set(GameState::RoundOver);
+}
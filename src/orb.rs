@@ -1,38 +1,170 @@
+use std::f32::consts::TAU;
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_rapier2d::prelude::*;
 use rand::Rng;
 
-use crate::{components::Hp, ship::Ship, GameState};
+use crate::{
+    components::{Hp, RoundEntity},
+    mode::ARENA_SIZE,
+    round::MatchRng,
+    ship::Ship,
+    weapon::Upgrade,
+    GameState,
+};
 
 struct OrbConfig {
     max_orbs: usize,
+    drift_speed: f32,
+    orbit_radius: f32,
+    orbit_speed: f32,
+    magnet_radius: f32,
+    magnet_strength: f32,
+    fragments: usize,
+    /// Chance that a spawned orb upgrades weapons instead of giving energy.
+    upgrade_chance: f64,
 }
 
 #[derive(Default)]
 struct OrbHandles {
-    handles: Vec<Handle<Image>>,
+    handles: HashMap<OrbKind, Handle<Image>>,
 }
 
 #[derive(Default, Debug, Component)]
 pub struct Orb;
 
+/// What a ship gets for collecting an orb.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrbKind {
+    Energy,
+    Magnet,
+    Upgrade(Upgrade),
+}
+
+impl OrbKind {
+    /// Energy first, the rest are the rarer upgrades.
+    const ALL: [OrbKind; 6] = [
+        OrbKind::Energy,
+        OrbKind::Magnet,
+        OrbKind::Upgrade(Upgrade::FireRate),
+        OrbKind::Upgrade(Upgrade::Damage),
+        OrbKind::Upgrade(Upgrade::ProjectileCount),
+        OrbKind::Upgrade(Upgrade::ProjectileSpeed),
+    ];
+
+    pub fn color(&self) -> Color {
+        match self {
+            OrbKind::Energy => Color::LIME_GREEN,
+            OrbKind::Magnet => Color::FUCHSIA,
+            OrbKind::Upgrade(_) => Color::GOLD,
+        }
+    }
+
+    fn texture(&self) -> &'static str {
+        match self {
+            OrbKind::Energy => "orbs/Airless.png",
+            OrbKind::Magnet => "orbs/Orb of Venom.png",
+            OrbKind::Upgrade(Upgrade::FireRate) => "orbs/Orb of Light.png",
+            OrbKind::Upgrade(Upgrade::Damage) => "orbs/Orb of Flame.png",
+            OrbKind::Upgrade(Upgrade::ProjectileCount) => "orbs/Orb of Frost.png",
+            OrbKind::Upgrade(Upgrade::ProjectileSpeed) => "orbs/Orb of Air.png",
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct OrbTimer(pub Timer);
 
+/// Spawns an orb at `position`, e.g. where an enemy was destroyed.
+pub struct OrbDrop {
+    pub position: Vec2,
+}
+
+/// Multiplies how often orbs spawn and how many can be around at once.
+pub struct OrbSpawnRate(pub f32);
+
+impl Default for OrbSpawnRate {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Orb moves in a straight line and bounces off the arena edges.
+#[derive(Component, Debug)]
+pub struct Drift(pub Vec2);
+
+/// Orb circles around `center`.
+#[derive(Component, Debug)]
+pub struct Orbit {
+    pub center: Vec2,
+    pub radius: f32,
+    pub angular_speed: f32,
+    pub angle: f32,
+}
+
+/// Orb is pulled towards ships closer than `radius`.
+#[derive(Component, Debug)]
+pub struct Attracted {
+    pub radius: f32,
+    pub strength: f32,
+}
+
+/// Magnet upgrade of a ship, multiplies the radius in which orbs are attracted.
+#[derive(Component, Debug)]
+pub struct Magnet(pub f32);
+
+impl Default for Magnet {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl Magnet {
+    const STEP: f32 = 0.5;
+    const MAX: f32 = 2.5;
+
+    /// Widens the radius by a step for every magnet orb collected.
+    pub fn level_up(&mut self) {
+        self.0 = (self.0 + Self::STEP).min(Self::MAX);
+    }
+}
+
+#[derive(Component)]
+struct Fragment {
+    velocity: Vec2,
+    timer: Timer,
+}
+
 pub struct OrbPlugin;
 
 impl Plugin for OrbPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<OrbHandles>()
-            .insert_resource(OrbConfig { max_orbs: 5 })
+            .init_resource::<OrbSpawnRate>()
+            .add_event::<OrbDrop>()
+            .insert_resource(OrbConfig {
+                max_orbs: 5,
+                drift_speed: 40.0,
+                orbit_radius: 120.0,
+                orbit_speed: 0.8,
+                magnet_radius: 150.0,
+                magnet_strength: 200.0,
+                fragments: 6,
+                upgrade_chance: 0.2,
+            })
             .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Countdown).with_system(reset_timer))
             .add_system_set(
                 SystemSet::on_update(GameState::Playing)
                     .with_system(tick)
+                    .with_system(drop_orbs)
                     .with_system(collision)
-                    .with_system(hp),
+                    .with_system(drift)
+                    .with_system(orbit)
+                    .with_system(attract.after(drift).after(orbit))
+                    .with_system(hp)
+                    .with_system(fragments),
             );
     }
 }
@@ -42,28 +174,45 @@ fn setup(mut commands: Commands, mut handles: ResMut<OrbHandles>, asset_server:
         .spawn()
         .insert(OrbTimer(Timer::new(Duration::from_secs(1), true)));
 
-    handles.handles.push(asset_server.load("orbs/Airless.png"));
+    for kind in OrbKind::ALL {
+        handles
+            .handles
+            .insert(kind, asset_server.load(kind.texture()));
+    }
+}
+
+/// Every round starts the spawns over, like the [`MatchRng`] they draw from.
+fn reset_timer(mut timer: Query<&mut OrbTimer>) {
+    let mut timer = timer.single_mut();
+    timer.0.set_duration(Duration::from_secs(1));
+    timer.0.reset();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn tick(
     mut commands: Commands,
-    windows: Res<Windows>,
     handles: Res<OrbHandles>,
     mut timer: Query<&mut OrbTimer>,
     orb_config: Res<OrbConfig>,
+    spawn_rate: Res<OrbSpawnRate>,
     orbs: Query<&Orb>,
     time: Res<Time>,
+    mut rng: ResMut<MatchRng>,
 ) {
     let mut timer = timer.single_mut();
     timer.0.tick(time.delta());
     if timer.0.just_finished() {
-        if orbs.iter().len() < orb_config.max_orbs {
-            spawn_orb(&mut commands, &windows, &handles);
+        let max_orbs = (orb_config.max_orbs as f32 * spawn_rate.0).round() as usize;
+        if orbs.iter().len() < max_orbs {
+            let position = Vec2::new(
+                rng.0.gen_range(-0.5..0.5) * ARENA_SIZE.x,
+                rng.0.gen_range(-0.5..0.5) * ARENA_SIZE.y,
+            );
+            spawn_orb(&mut commands, position, &handles, &orb_config, &mut rng.0);
         }
-        let mut rng = rand::thread_rng();
-        timer
-            .0
-            .set_duration(Duration::from_secs(rng.gen_range(1..=10)));
+        timer.0.set_duration(Duration::from_secs_f32(
+            rng.0.gen_range(1.0..=10.0) / spawn_rate.0,
+        ));
     }
 }
 
@@ -84,33 +233,175 @@ fn collision(
     }
 }
 
-fn spawn_orb(commands: &mut Commands, windows: &Res<Windows>, handles: &Res<OrbHandles>) {
-    let w = windows.primary();
-    let width = w.width();
-    let height = w.height();
-    let mut rng = rand::thread_rng();
-    commands
-        .spawn()
-        .insert(Orb)
+fn drop_orbs(
+    mut commands: Commands,
+    mut drops: EventReader<OrbDrop>,
+    handles: Res<OrbHandles>,
+    orb_config: Res<OrbConfig>,
+    mut rng: ResMut<MatchRng>,
+) {
+    for drop in drops.iter() {
+        spawn_orb(
+            &mut commands,
+            drop.position,
+            &handles,
+            &orb_config,
+            &mut rng.0,
+        );
+    }
+}
+
+fn spawn_orb(
+    commands: &mut Commands,
+    position: Vec2,
+    handles: &Res<OrbHandles>,
+    orb_config: &OrbConfig,
+    rng: &mut impl Rng,
+) {
+    let kind = if rng.gen_bool(orb_config.upgrade_chance) {
+        OrbKind::ALL[rng.gen_range(1..OrbKind::ALL.len())]
+    } else {
+        OrbKind::Energy
+    };
+
+    let mut orb = commands.spawn();
+    orb.insert(Orb)
+        .insert(RoundEntity)
+        .insert(kind)
         .insert(Hp(10.0))
         .insert_bundle(SpriteBundle {
-            transform: Transform::from_xyz(
-                rng.gen_range(-0.5..0.5) * width,
-                rng.gen_range(-0.5..0.5) * height,
-                0.0,
-            ),
-            texture: handles.handles[0].clone(),
+            transform: Transform::from_translation(position.extend(0.0)),
+            texture: handles.handles[&kind].clone(),
             ..default()
         })
-        .insert(RigidBody::Dynamic)
+        .insert(RigidBody::KinematicPositionBased)
         .insert(Collider::ball(32.0))
-        .insert(Sensor);
+        .insert(Sensor)
+        .insert(Attracted {
+            radius: orb_config.magnet_radius,
+            strength: orb_config.magnet_strength,
+        });
+
+    match rng.gen_range(0..3) {
+        0 => {
+            let angle = rng.gen_range(0.0..TAU);
+            orb.insert(Drift(Vec2::from_angle(angle) * orb_config.drift_speed));
+        }
+        1 => {
+            let angle = rng.gen_range(0.0..TAU);
+            orb.insert(Orbit {
+                center: position - Vec2::from_angle(angle) * orb_config.orbit_radius,
+                radius: orb_config.orbit_radius,
+                angular_speed: orb_config.orbit_speed,
+                angle,
+            });
+        }
+        _ => {}
+    }
+}
+
+fn drift(windows: Res<Windows>, mut orbs: Query<(&mut Transform, &mut Drift)>, time: Res<Time>) {
+    let w = windows.primary();
+    let half = Vec2::new(w.width(), w.height()) / 2.0;
+    for (mut transform, mut drift) in orbs.iter_mut() {
+        transform.translation += (drift.0 * time.delta_seconds()).extend(0.0);
+        if transform.translation.x.abs() > half.x {
+            transform.translation.x = transform.translation.x.clamp(-half.x, half.x);
+            drift.0.x = -drift.0.x;
+        }
+        if transform.translation.y.abs() > half.y {
+            transform.translation.y = transform.translation.y.clamp(-half.y, half.y);
+            drift.0.y = -drift.0.y;
+        }
+    }
 }
 
-fn hp(mut commands: Commands, hp: Query<(Entity, &Hp), With<Orb>>) {
-    for (e, hp) in hp.iter() {
+fn orbit(mut orbs: Query<(&mut Transform, &mut Orbit)>, time: Res<Time>) {
+    for (mut transform, mut orbit) in orbs.iter_mut() {
+        orbit.angle = (orbit.angle + orbit.angular_speed * time.delta_seconds()) % TAU;
+        let position = orbit.center + Vec2::from_angle(orbit.angle) * orbit.radius;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+fn attract(
+    mut orbs: Query<(&mut Transform, &Attracted, Option<&mut Orbit>), Without<Ship>>,
+    ships: Query<(&Transform, Option<&Magnet>), With<Ship>>,
+    time: Res<Time>,
+) {
+    for (mut transform, attracted, orbit) in orbs.iter_mut() {
+        let position = transform.translation.truncate();
+        let nearest = ships
+            .iter()
+            .filter_map(|(t, magnet)| {
+                let radius = attracted.radius * magnet.map_or(1.0, |m| m.0);
+                let offset = t.translation.truncate() - position;
+                let distance = offset.length();
+                (distance < radius && distance > 0.0).then(|| (offset, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((offset, distance)) = nearest {
+            let step = (attracted.strength * time.delta_seconds()).min(distance);
+            let delta = offset / distance * step;
+            transform.translation += delta.extend(0.0);
+            // Drag the orbit along, otherwise the orb snaps back next frame.
+            if let Some(mut orbit) = orbit {
+                orbit.center += delta;
+            }
+        }
+    }
+}
+
+fn hp(
+    mut commands: Commands,
+    hp: Query<(Entity, &Hp, &Transform, &Handle<Image>), With<Orb>>,
+    orb_config: Res<OrbConfig>,
+) {
+    for (e, hp, transform, texture) in hp.iter() {
         if hp.0 < 0.0 {
             commands.entity(e).despawn();
+            spawn_fragments(&mut commands, transform, texture, orb_config.fragments);
+        }
+    }
+}
+
+fn spawn_fragments(
+    commands: &mut Commands,
+    transform: &Transform,
+    texture: &Handle<Image>,
+    count: usize,
+) {
+    let mut rng = rand::thread_rng();
+    for i in 0..count {
+        let angle = TAU * i as f32 / count as f32 + rng.gen_range(-0.3..0.3);
+        commands
+            .spawn_bundle(SpriteBundle {
+                transform: transform.with_scale(Vec3::splat(0.25)),
+                texture: texture.clone(),
+                ..default()
+            })
+            .insert(RoundEntity)
+            .insert(Fragment {
+                velocity: Vec2::from_angle(angle) * rng.gen_range(60.0..160.0),
+                timer: Timer::new(Duration::from_millis(800), false),
+            });
+    }
+}
+
+fn fragments(
+    mut commands: Commands,
+    mut fragments: Query<(Entity, &mut Transform, &mut Sprite, &mut Fragment)>,
+    time: Res<Time>,
+) {
+    for (e, mut transform, mut sprite, mut fragment) in fragments.iter_mut() {
+        fragment.timer.tick(time.delta());
+        if fragment.timer.finished() {
+            commands.entity(e).despawn();
+            continue;
         }
+        transform.translation += (fragment.velocity * time.delta_seconds()).extend(0.0);
+        sprite.color.set_a(fragment.timer.percent_left());
     }
 }
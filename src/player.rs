@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 use crate::{
-    ship::{spawn_ship, Accelerate, Acceleration, FireWeapon, Ship, ShipConfig},
+    ship::{spawn_ship, Accelerate, Acceleration, FireSecondary, FireWeapon, Ship, ShipConfig},
     utils,
 };
 use bevy_rapier2d::prelude::*;
@@ -11,18 +11,47 @@ pub struct Player {
     pub handle: usize,
 }
 
+/// Handles of the players controlled on this machine.
+#[derive(Default, Debug)]
+pub struct LocalPlayers(pub Vec<usize>);
+
+impl Player {
+    pub fn name(&self) -> String {
+        format!("Player {}", self.handle + 1)
+    }
+
+    pub fn color(&self) -> Color {
+        match self.handle % 4 {
+            0 => Color::rgb(0., 0.47, 1.),
+            1 => Color::rgb(0., 0.4, 0.),
+            2 => Color::rgb(0.9, 0.3, 0.1),
+            _ => Color::rgb(0.8, 0.7, 0.),
+        }
+    }
+}
+
 // fn spawn_player(
 //     mut commands: Commands,
 //     asset_server: Res<AssetServer>,
 //     mut textures: ResMut<Assets<TextureAtlas>>,
 // ) {
 //     let ship_config = ShipConfig {
+//         name: "Player 1",
 //         acceleration: 140.0,
 //         ship_sprite: "ships/1.png",
 //         exhaust_sprite_sheet: "ships/exhaust/exhaust1.png",
 //         max_energy: 100.0,
 //         energy_decay: 0.8,
 //         energy_start_value: 5.0,
+//         hull: 20.0,
+//         weapons: vec![WeaponMount {
+//             definition: "weapons/blaster.weapon.ron",
+//             offset: Vec2::ZERO,
+//             group: WeaponGroup::Primary,
+//         }],
+//         fire_mode: FireMode::Together,
+//         team: Team(0),
+//         tint: Color::WHITE,
 //     };
 
 //     let ship = spawn_ship(ship_config, &mut commands, &asset_server, &mut textures);
@@ -74,4 +103,10 @@ fn user_input(
     } else if keyboard_input.just_released(KeyCode::Space) {
         commands.entity(ship.single().0).remove::<FireWeapon>();
     }
+
+    if keyboard_input.just_pressed(KeyCode::E) {
+        commands.entity(ship.single().0).insert(FireSecondary);
+    } else if keyboard_input.just_released(KeyCode::E) {
+        commands.entity(ship.single().0).remove::<FireSecondary>();
+    }
 }
@@ -0,0 +1,347 @@
+//! Flow of a match: menu, lobby, countdown, playing, round over and results.
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    components::{RoundEntity, Team},
+    events::ShipDestroyed,
+    hud::{Announcements, Countdown, MatchTimer},
+    mode::MatchConfig,
+    player::Player,
+    ship::Ship,
+    weapon::ProjectilePool,
+    GameState,
+};
+
+const COUNTDOWN_SECS: u32 = 3;
+const ROUND_OVER_DELAY: Duration = Duration::from_secs(3);
+
+pub struct RoundPlugin;
+
+impl Plugin for RoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Round>()
+            .init_resource::<MatchRng>()
+            .init_resource::<MatchScores>()
+            .init_resource::<MatchOutcome>()
+            .insert_resource(RoundOverTimer(Timer::new(ROUND_OVER_DELAY, false)))
+            .add_system(count_kills)
+            .add_system_set(
+                SystemSet::on_enter(GameState::MainMenu)
+                    .with_system(cleanup)
+                    .with_system(despawn_players)
+                    .with_system(clear_announcements),
+            )
+            .add_system_set(SystemSet::on_update(GameState::MainMenu).with_system(leave_menu))
+            .add_system_set(SystemSet::on_exit(GameState::Lobby).with_system(start_match))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Countdown)
+                    .with_system(cleanup)
+                    .with_system(start_round),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Countdown).with_system(start_playing))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing).with_system(last_ship_standing),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::RoundOver).with_system(end_round))
+            .add_system_set(SystemSet::on_update(GameState::RoundOver).with_system(next_round))
+            .add_system_set(SystemSet::on_update(GameState::Results).with_system(leave_results));
+    }
+}
+
+/// The round being played, counting from 1.
+#[derive(Default)]
+pub struct Round {
+    pub current: u32,
+}
+
+/// Set by the game mode once the match is decided.
+#[derive(Default)]
+pub struct MatchOutcome {
+    pub decided: bool,
+    /// `None` for a draw.
+    pub winner: Option<String>,
+    /// Shown instead of the winner, e.g. how far a cooperative match got.
+    pub summary: Option<String>,
+}
+
+impl MatchOutcome {
+    /// Ends the match in favor of the best player, or as a draw on a tie.
+    pub fn decide(&mut self, scores: &MatchScores) {
+        self.decided = true;
+        self.winner = scores.leader().map(str::to_string);
+    }
+}
+
+/// Points per player, or per team in team matches, over all rounds of the
+/// match. Kills unless the game mode scores something else.
+#[derive(Default)]
+pub struct MatchScores {
+    points: HashMap<String, u32>,
+    teams: bool,
+}
+
+impl MatchScores {
+    /// Credits the player's team instead of the player in team matches.
+    pub fn add(&mut self, name: &str, team: Option<&Team>, points: u32) {
+        let name = match team {
+            Some(team) if self.teams => team.name(),
+            _ => name,
+        };
+        *self.points.entry(name.to_string()).or_default() += points;
+    }
+
+    /// Points of the player, or of their team in team matches.
+    pub fn of(&self, name: &str, team: Option<&Team>) -> u32 {
+        let name = match team {
+            Some(team) if self.teams => team.name(),
+            _ => name,
+        };
+        self.points.get(name).copied().unwrap_or_default()
+    }
+
+    /// Names and points, best first.
+    pub fn ranking(&self) -> Vec<(&str, u32)> {
+        let mut ranking: Vec<_> = self.points.iter().map(|(n, k)| (n.as_str(), *k)).collect();
+        ranking.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        ranking
+    }
+
+    /// The player with the most points, if nobody else has as many.
+    pub fn leader(&self) -> Option<&str> {
+        match self.ranking().as_slice() {
+            [(name, _)] => Some(*name),
+            [(name, best), (_, second), ..] if best > second => Some(*name),
+            _ => None,
+        }
+    }
+}
+
+/// Randomness all peers have to agree on, e.g. where the capture zone moves.
+/// Reseeded from the match seed at the start of every round.
+pub struct MatchRng(pub StdRng);
+
+impl Default for MatchRng {
+    fn default() -> Self {
+        Self(StdRng::seed_from_u64(0))
+    }
+}
+
+struct RoundOverTimer(Timer);
+
+/// Whether Enter or Space was just pressed. The press is used up, so the
+/// screen coming next doesn't see it too.
+pub fn confirm_pressed(keys: &mut Input<KeyCode>) -> bool {
+    let mut pressed = false;
+    for key in [KeyCode::Return, KeyCode::Space] {
+        pressed |= keys.clear_just_pressed(key);
+    }
+    pressed
+}
+
+fn leave_menu(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if confirm_pressed(&mut keys) {
+        let _ = state.set(GameState::Lobby);
+    }
+}
+
+fn leave_results(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if confirm_pressed(&mut keys) {
+        let _ = state.set(GameState::MainMenu);
+    }
+}
+
+/// Despawns everything that belongs to the last round.
+fn cleanup(
+    mut commands: Commands,
+    entities: Query<Entity, With<RoundEntity>>,
+    mut pool: ResMut<ProjectilePool>,
+) {
+    for e in entities.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    pool.clear();
+}
+
+fn despawn_players(mut commands: Commands, players: Query<Entity, With<Player>>) {
+    for e in players.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn clear_announcements(mut announcements: ResMut<Announcements>) {
+    announcements.clear();
+}
+
+fn start_match(
+    config: Res<MatchConfig>,
+    mut round: ResMut<Round>,
+    mut scores: ResMut<MatchScores>,
+    mut outcome: ResMut<MatchOutcome>,
+    mut timer: ResMut<MatchTimer>,
+) {
+    round.current = 0;
+    *scores = MatchScores {
+        points: HashMap::default(),
+        teams: config.teams(),
+    };
+    *outcome = MatchOutcome::default();
+    timer.reset(config.time_limit);
+}
+
+fn start_round(
+    config: Res<MatchConfig>,
+    mut round: ResMut<Round>,
+    mut rng: ResMut<MatchRng>,
+    mut countdown: ResMut<Countdown>,
+    mut announcements: ResMut<Announcements>,
+) {
+    round.current += 1;
+    rng.0 = StdRng::seed_from_u64(config.seed.wrapping_add(round.current as u64));
+    countdown.start(COUNTDOWN_SECS);
+    match config.rounds {
+        Some(total) => announcements.push(format!("Round {} of {}", round.current, total)),
+        None => announcements.push(format!("Round {}", round.current)),
+    }
+}
+
+fn start_playing(countdown: Res<Countdown>, mut state: ResMut<State<GameState>>) {
+    if countdown.finished() {
+        let _ = state.set(GameState::Playing);
+    }
+}
+
+fn count_kills(
+    config: Res<MatchConfig>,
+    mut events: EventReader<ShipDestroyed>,
+    teams: Query<&Team>,
+    players: Query<(), With<Player>>,
+    mut scores: ResMut<MatchScores>,
+) {
+    for event in events.iter() {
+        if !config.mode.scores_kills() {
+            continue;
+        }
+        let (killer, killer_name) = match (event.killer, &event.killer_name) {
+            (Some(killer), Some(name)) => (killer, name),
+            _ => continue,
+        };
+        // Only players score, not enemy ships.
+        if !players.contains(killer) {
+            continue;
+        }
+        let team = teams.get(killer).ok();
+        // Friendly fire doesn't score.
+        if team.is_some() && team == teams.get(event.ship).ok() {
+            continue;
+        }
+        scores.add(killer_name, team, 1);
+    }
+}
+
+/// Ends the round once a kill leaves ships of at most one team alive.
+fn last_ship_standing(
+    config: Res<MatchConfig>,
+    mut events: EventReader<ShipDestroyed>,
+    ships: Query<(Entity, &Team), With<Ship>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !config.mode.last_team_standing() {
+        return;
+    }
+    let destroyed: Vec<Entity> = events.iter().map(|e| e.ship).collect();
+    if destroyed.is_empty() {
+        return;
+    }
+    // Destroyed ships are only despawned at the end of the frame.
+    let alive: HashSet<Team> = ships
+        .iter()
+        .filter(|(e, _)| !destroyed.contains(e))
+        .map(|(_, team)| *team)
+        .collect();
+    if alive.len() <= 1 {
+        let _ = state.set(GameState::RoundOver);
+    }
+}
+
+fn end_round(
+    round: Res<Round>,
+    mut timer: ResMut<RoundOverTimer>,
+    mut announcements: ResMut<Announcements>,
+) {
+    announcements.push(format!("Round {} over", round.current));
+    timer.0.reset();
+}
+
+fn next_round(
+    mut timer: ResMut<RoundOverTimer>,
+    config: Res<MatchConfig>,
+    round: Res<Round>,
+    scores: Res<MatchScores>,
+    mut outcome: ResMut<MatchOutcome>,
+    mut state: ResMut<State<GameState>>,
+    time: Res<Time>,
+) {
+    if !timer.0.tick(time.delta()).finished() {
+        return;
+    }
+    if !outcome.decided && config.rounds.map_or(false, |total| round.current >= total) {
+        outcome.decide(&scores);
+    }
+    let next = if outcome.decided {
+        GameState::Results
+    } else {
+        GameState::Countdown
+    };
+    let _ = state.set(next);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scores(teams: bool, points: &[(&str, u32)]) -> MatchScores {
+        let mut scores = MatchScores {
+            teams,
+            ..Default::default()
+        };
+        for (name, points) in points {
+            scores.add(name, None, *points);
+        }
+        scores
+    }
+
+    #[test]
+    fn ranking_is_best_first_then_by_name() {
+        let scores = scores(false, &[("Player 2", 1), ("Player 1", 3), ("Player 3", 1)]);
+        assert_eq!(
+            scores.ranking(),
+            vec![("Player 1", 3), ("Player 2", 1), ("Player 3", 1)]
+        );
+        assert_eq!(scores.leader(), Some("Player 1"));
+    }
+
+    #[test]
+    fn tie_has_no_leader() {
+        let scores = scores(false, &[("Player 1", 2), ("Player 2", 2)]);
+        assert_eq!(scores.leader(), None);
+        assert_eq!(MatchScores::default().leader(), None);
+    }
+
+    #[test]
+    fn team_matches_credit_the_team() {
+        let mut scores = scores(true, &[]);
+        scores.add("Player 1", Some(&Team(0)), 1);
+        scores.add("Player 3", Some(&Team(0)), 2);
+
+        assert_eq!(scores.ranking(), vec![(Team(0).name(), 3)]);
+        assert_eq!(scores.of("Player 1", Some(&Team(0))), 3);
+        assert_eq!(scores.of("Player 2", Some(&Team(1))), 0);
+    }
+}
@@ -3,25 +3,57 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    orb::Orb,
+    components::{Hp, MaxHp, RoundEntity, Score, Team},
+    events::{OrbCollected, ShipDestroyed},
+    orb::{Magnet, Orb, OrbKind},
     player::Player,
     ship::energy::EnergyBundle,
-    weapon::{self, Damage, FireRate, ProjectileSpeed, Weapon, WeaponBundle},
-    Animation, AnimationState,
+    weapon::{self, DamageEvent, FireRate, Weapon, WeaponBundle, WeaponGroup, WeaponUpgrades},
+    Animation, AnimationState, GameState,
 };
 
+pub mod control;
 pub mod energy;
+pub use control::ShipInput;
 pub use energy::Energy;
 
 use self::energy::{EnergyDecay, EnergyTimer};
 
+/// Collision group shared by all ships without a player, player ships get
+/// one per handle.
+const ENEMY_GROUP: u32 = 1 << 8;
+
 pub struct ShipConfig<'a> {
+    pub name: &'a str,
     pub acceleration: f32,
     pub ship_sprite: &'a str,
     pub exhaust_sprite_sheet: &'a str,
     pub max_energy: f32,
     pub energy_decay: f32,
     pub energy_start_value: f32,
+    pub hull: f32,
+    pub weapons: Vec<WeaponMount<'a>>,
+    pub fire_mode: FireMode,
+    /// Multiplied with the ship and exhaust sprites, e.g. the team color.
+    pub tint: Color,
+    pub team: Team,
+}
+
+/// A weapon attached to the ship at `offset` from the ship's center.
+pub struct WeaponMount<'a> {
+    pub definition: &'a str,
+    pub offset: Vec2,
+    pub group: WeaponGroup,
+}
+
+/// How the weapons of a ship are triggered.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FireMode {
+    /// All weapons fire at the same time.
+    #[default]
+    Together,
+    /// Weapons take turns, evenly spread over one fire period.
+    Alternate,
 }
 
 #[derive(Component)]
@@ -39,6 +71,48 @@ pub struct Accelerate;
 #[derive(Component)]
 pub struct FireWeapon;
 
+/// Tuning of the damage ships take when crashing into each other.
+pub struct RamConfig {
+    /// Relative speed below which collisions are harmless.
+    pub min_speed: f32,
+    /// Damage per unit of relative speed above `min_speed`.
+    pub damage_factor: f32,
+    /// Contact force needed before rapier reports a collision at all.
+    pub force_threshold: f32,
+    /// Seconds a ship is immune to ramming after being hit.
+    pub cooldown: f32,
+}
+
+impl Default for RamConfig {
+    fn default() -> Self {
+        Self {
+            min_speed: 60.0,
+            damage_factor: 0.05,
+            force_threshold: 500.0,
+            cooldown: 0.5,
+        }
+    }
+}
+
+#[derive(Component)]
+struct RamCooldown(Timer);
+
+#[derive(Component)]
+pub struct FireSecondary;
+
+/// Ship component that forwards firing to the weapons of one [`WeaponGroup`].
+pub trait Trigger: Component {
+    const GROUP: WeaponGroup;
+}
+
+impl Trigger for FireWeapon {
+    const GROUP: WeaponGroup = WeaponGroup::Primary;
+}
+
+impl Trigger for FireSecondary {
+    const GROUP: WeaponGroup = WeaponGroup::Secondary;
+}
+
 pub fn spawn_ship(
     ship_config: ShipConfig,
     commands: &mut Commands,
@@ -48,12 +122,23 @@ pub fn spawn_ship(
     commands
         .spawn()
         .insert(Ship)
-        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert(RoundEntity)
+        .insert(ActiveEvents::COLLISION_EVENTS | ActiveEvents::CONTACT_FORCE_EVENTS)
         .insert_bundle(SpriteBundle {
             texture: asset_server.load(ship_config.ship_sprite),
+            sprite: Sprite {
+                color: ship_config.tint,
+                ..default()
+            },
             ..default()
         })
         .insert(Acceleration(ship_config.acceleration))
+        .insert(ship_config.fire_mode)
+        .insert(Name::new(ship_config.name.to_string()))
+        .insert(ship_config.team)
+        .insert(Score::default())
+        .insert(Hp(ship_config.hull))
+        .insert(MaxHp(ship_config.hull))
         .insert_bundle(EnergyBundle {
             energy: Energy {
                 max: ship_config.max_energy,
@@ -66,6 +151,7 @@ pub fn spawn_ship(
         .with_children(|p| {
             p.spawn_bundle(ExhaustAnimationBundle::new(
                 ship_config.exhaust_sprite_sheet,
+                ship_config.tint,
                 asset_server,
                 textures,
             ))
@@ -76,12 +162,14 @@ pub fn spawn_ship(
             }))
             .insert(Exhaust);
 
-            p.spawn_bundle(WeaponBundle {
-                fire_rate: FireRate::new(5.0),
-                damage: Damage(1.3),
-                speed: ProjectileSpeed(250.0),
-                ..Default::default()
-            });
+            for mount in ship_config.weapons.iter() {
+                p.spawn_bundle(WeaponBundle {
+                    group: mount.group,
+                    definition: asset_server.load(mount.definition),
+                    transform: Transform::from_translation(mount.offset.extend(0.0)),
+                    ..Default::default()
+                });
+            }
         })
         .id()
 }
@@ -97,6 +185,7 @@ pub struct ExhaustAnimationBundle {
 impl ExhaustAnimationBundle {
     pub fn new(
         exhaust_sprite: &str,
+        tint: Color,
         asset_server: &AssetServer,
         textures: &mut Assets<TextureAtlas>,
     ) -> Self {
@@ -113,6 +202,10 @@ impl ExhaustAnimationBundle {
                     3,
                     1,
                 )),
+                sprite: TextureAtlasSprite {
+                    color: tint,
+                    ..Default::default()
+                },
                 visibility: Visibility { is_visible: false },
                 ..Default::default()
             },
@@ -126,8 +219,10 @@ impl ExhaustAnimationBundle {
 pub struct Kinematic {
     rigid_body: RigidBody,
     ext_force: ExternalForce,
+    ext_impulse: ExternalImpulse,
     velocity: Velocity,
     collider: Collider,
+    mass: ReadMassProperties,
 }
 
 impl Kinematic {
@@ -138,8 +233,10 @@ impl Kinematic {
                 force: Vec2::new(0.0, 0.0),
                 torque: 0.0,
             },
+            ext_impulse: ExternalImpulse::default(),
             velocity: Velocity::default(),
             collider: Collider::ball(50.0),
+            mass: ReadMassProperties::default(),
         }
     }
 }
@@ -148,11 +245,31 @@ pub struct ShipPlugin;
 
 impl Plugin for ShipPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(show_exhaust)
-            .add_system(collision_event)
-            .add_system(remove_exhaust)
-            .add_system(fire_weapon)
-            .add_system(stop_fire_weapon);
+        app.init_resource::<RamConfig>()
+            .add_system(set_ram_threshold)
+            .add_system(set_collision_groups)
+            // Inserting on a despawned entity panics, so ships go only after
+            // everything that adds components to them or their weapons.
+            .add_system(
+                destroy
+                    .after(control::apply_input)
+                    .after(collision_event)
+                    .after(fire_weapon::<FireWeapon>)
+                    .after(fire_weapon::<FireSecondary>),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(control::apply_input)
+                    .with_system(show_exhaust)
+                    .with_system(ram)
+                    .with_system(tick_ram_cooldown)
+                    .with_system(collision_event)
+                    .with_system(remove_exhaust)
+                    .with_system(fire_weapon::<FireWeapon>)
+                    .with_system(stop_fire_weapon::<FireWeapon>)
+                    .with_system(fire_weapon::<FireSecondary>)
+                    .with_system(stop_fire_weapon::<FireSecondary>),
+            );
     }
 }
 
@@ -175,7 +292,11 @@ fn remove_exhaust(
     mut exhaust: Query<&mut Visibility, With<Exhaust>>,
 ) {
     for e in q.iter() {
-        let (_, children) = ship.get(e).unwrap();
+        // The ship may have been destroyed while accelerating.
+        let (_, children) = match ship.get(e) {
+            Ok(ship) => ship,
+            Err(_) => continue,
+        };
         for &v in children.iter() {
             if let Ok(mut v) = exhaust.get_mut(v) {
                 v.is_visible = false;
@@ -185,50 +306,179 @@ fn remove_exhaust(
 }
 
 fn collision_event(
+    mut commands: Commands,
     mut collisions: EventReader<CollisionEvent>,
-    mut ship_energy: Query<(&Ship, &mut Energy), With<Player>>,
-    orbs: Query<&Orb>,
+    mut ship_energy: Query<(&Ship, &mut Energy, Option<&mut Magnet>), With<Player>>,
+    orbs: Query<&OrbKind, With<Orb>>,
+    mut upgrades: ResMut<WeaponUpgrades>,
+    mut collected: EventWriter<OrbCollected>,
 ) {
     for collision in collisions.iter() {
         if let CollisionEvent::Started(e0, e1, _) = collision {
-            if let Ok((_, mut energy)) = ship_energy.get_mut(*e0) {
-                if let Ok(_) = orbs.get(*e1) {
-                    (*energy).increase(10.0);
-                }
-            } else if let Ok((_, mut energy)) = ship_energy.get_mut(*e1) {
-                if let Ok(_) = orbs.get(*e0) {
-                    (*energy).increase(10.0);
+            for (ship, orb) in [(*e0, *e1), (*e1, *e0)] {
+                if let (Ok((_, mut energy, magnet)), Ok(kind)) =
+                    (ship_energy.get_mut(ship), orbs.get(orb))
+                {
+                    match kind {
+                        OrbKind::Energy => (*energy).increase(10.0),
+                        OrbKind::Magnet => match magnet {
+                            Some(mut magnet) => magnet.level_up(),
+                            None => {
+                                let mut magnet = Magnet::default();
+                                magnet.level_up();
+                                commands.entity(ship).insert(magnet);
+                            }
+                        },
+                        OrbKind::Upgrade(upgrade) => upgrades.level_up(ship, *upgrade),
+                    }
+                    collected.send(OrbCollected { ship, kind: *kind });
+                    break;
                 }
             }
         }
     }
 }
 
-fn fire_weapon(
+/// Despawns destroyed ships. Other plugins' systems that add to ships in
+/// `Update` are ordered `.before(ship::destroy)`.
+pub fn destroy(mut commands: Commands, mut destroyed: EventReader<ShipDestroyed>) {
+    for event in destroyed.iter() {
+        commands.entity(event.ship).despawn_recursive();
+    }
+}
+
+fn set_ram_threshold(
+    mut commands: Commands,
+    ships: Query<Entity, Added<Ship>>,
+    config: Res<RamConfig>,
+) {
+    for e in ships.iter() {
+        commands
+            .entity(e)
+            .insert(ContactForceEventThreshold(config.force_threshold));
+    }
+}
+
+/// Puts every ship into a collision group of its own, projectiles leave out
+/// the group of the ship that fired them so they can't hit it.
+fn set_collision_groups(
+    mut commands: Commands,
+    ships: Query<(Entity, Option<&Player>), Added<Ship>>,
+) {
+    for (e, player) in ships.iter() {
+        let group = player.map_or(ENEMY_GROUP, |p| 1 << p.handle.min(7));
+        commands
+            .entity(e)
+            .insert(CollisionGroups::new(group, u32::MAX));
+    }
+}
+
+/// Damages both ships of a hard collision, scaled by their relative velocity
+/// and the mass of the other ship.
+fn ram(
+    mut commands: Commands,
+    mut contacts: EventReader<ContactForceEvent>,
+    ships: Query<
+        (
+            &Velocity,
+            &ReadMassProperties,
+            &Transform,
+            Option<&RamCooldown>,
+        ),
+        With<Ship>,
+    >,
+    config: Res<RamConfig>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for contact in contacts.iter() {
+        let (e0, e1) = (contact.collider1, contact.collider2);
+        let (s0, s1) = match (ships.get(e0), ships.get(e1)) {
+            (Ok(s0), Ok(s1)) => (s0, s1),
+            _ => continue,
+        };
+        let relative_speed = (s0.0.linvel - s1.0.linvel).length();
+        if relative_speed < config.min_speed {
+            continue;
+        }
+        let (m0, m1) = (s0.1 .0.mass, s1.1 .0.mass);
+        let total_mass = m0 + m1;
+        if total_mass <= 0.0 {
+            continue;
+        }
+        let damage = (relative_speed - config.min_speed) * config.damage_factor;
+
+        for (target, (_, _, transform, cooldown), other, other_mass) in
+            [(e0, s0, e1, m1), (e1, s1, e0, m0)]
+        {
+            if cooldown.is_some() {
+                continue;
+            }
+            damage_events.send(DamageEvent {
+                target,
+                amount: damage * 2.0 * other_mass / total_mass,
+                source: Some(other),
+                position: transform.translation.truncate(),
+            });
+            commands
+                .entity(target)
+                .insert(RamCooldown(Timer::from_seconds(config.cooldown, false)));
+        }
+    }
+}
+
+fn tick_ram_cooldown(
     mut commands: Commands,
-    ship: Query<(&Ship, &FireWeapon, &Children), Added<FireWeapon>>,
-    weapon: Query<&Weapon>,
+    mut cooldowns: Query<(Entity, &mut RamCooldown)>,
+    time: Res<Time>,
 ) {
-    for (_, _, children) in ship.iter() {
-        for &c in children {
-            if let Ok(_) = weapon.get(c) {
-                commands.entity(c).insert(weapon::FireWeapon);
+    for (e, mut cooldown) in cooldowns.iter_mut() {
+        cooldown.0.tick(time.delta());
+        if cooldown.0.finished() {
+            commands.entity(e).remove::<RamCooldown>();
+        }
+    }
+}
+
+fn fire_weapon<T: Trigger>(
+    mut commands: Commands,
+    ship: Query<(&Ship, &FireMode, &Children), Added<T>>,
+    mut weapon: Query<(&mut FireRate, &WeaponGroup), With<Weapon>>,
+) {
+    for (_, fire_mode, children) in ship.iter() {
+        let weapons: Vec<_> = children
+            .iter()
+            .filter(|&&c| matches!(weapon.get(c), Ok((_, group)) if *group == T::GROUP))
+            .collect();
+        let n = weapons.len() as u32;
+        for (i, &&c) in weapons.iter().enumerate() {
+            if *fire_mode == FireMode::Alternate {
+                // Shift each weapon's timer phase so the shots interleave.
+                let (mut fire_rate, _) = weapon.get_mut(c).unwrap();
+                let timer = fire_rate.timer_mut();
+                let period = timer.duration();
+                timer.set_elapsed(period * i as u32 / n);
             }
+            commands.entity(c).insert(weapon::FireWeapon);
         }
     }
 }
 
-fn stop_fire_weapon(
+fn stop_fire_weapon<T: Trigger>(
     mut commands: Commands,
-    q: RemovedComponents<FireWeapon>,
+    q: RemovedComponents<T>,
     ship: Query<(&Ship, &Children)>,
-    weapon: Query<&Weapon>,
+    weapon: Query<&WeaponGroup, With<Weapon>>,
 ) {
     for e in q.iter() {
-        let (_, children) = ship.get(e).unwrap();
+        let (_, children) = match ship.get(e) {
+            Ok(ship) => ship,
+            Err(_) => continue,
+        };
         for &c in children {
-            if let Ok(_) = weapon.get(c) {
-                commands.entity(c).remove::<weapon::FireWeapon>();
+            if let Ok(&group) = weapon.get(c) {
+                if group == T::GROUP {
+                    commands.entity(c).remove::<weapon::FireWeapon>();
+                }
             }
         }
     }
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use super::{Accelerate, Acceleration, FireSecondary, FireWeapon, Ship};
+use crate::{utils, INPUT_FIRE, INPUT_FIRE_SECONDARY, INPUT_LEFT, INPUT_RIGHT, INPUT_UP};
+
+/// Controls of a ship in the same format as the network input, so ships can
+/// be flown by anything that produces it.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct ShipInput(pub u8);
+
+impl ShipInput {
+    pub fn pressed(&self, button: u8) -> bool {
+        self.0 & button != 0
+    }
+}
+
+pub fn apply_input(
+    mut commands: Commands,
+    mut ships: Query<
+        (
+            Entity,
+            &ShipInput,
+            &Transform,
+            &Acceleration,
+            &mut ExternalForce,
+            &mut Velocity,
+            Option<&Accelerate>,
+            Option<&FireWeapon>,
+            Option<&FireSecondary>,
+        ),
+        With<Ship>,
+    >,
+) {
+    for (e, input, transform, acceleration, mut force, mut velocity, accelerate, fire, secondary) in
+        ships.iter_mut()
+    {
+        if input.pressed(INPUT_LEFT) {
+            force.torque = 1.0;
+        } else if input.pressed(INPUT_RIGHT) {
+            force.torque = -1.0;
+        } else if force.torque != 0.0 {
+            force.torque = 0.0;
+            velocity.angvel = 0.0;
+        }
+
+        // Markers only change with the buttons, so `Added` and
+        // `RemovedComponents` see presses and releases.
+        let mut ship = commands.entity(e);
+        if input.pressed(INPUT_UP) {
+            let (dx, dy) = utils::direction(transform);
+            force.force = Vec2::new(dx, dy) * acceleration.0;
+            if accelerate.is_none() {
+                ship.insert(Accelerate);
+            }
+        } else if accelerate.is_some() || force.force != Vec2::ZERO {
+            force.force = Vec2::ZERO;
+            ship.remove::<Accelerate>();
+        }
+
+        match (input.pressed(INPUT_FIRE), fire.is_some()) {
+            (true, false) => {
+                ship.insert(FireWeapon);
+            }
+            (false, true) => {
+                ship.remove::<FireWeapon>();
+            }
+            _ => {}
+        }
+        match (input.pressed(INPUT_FIRE_SECONDARY), secondary.is_some()) {
+            (true, false) => {
+                ship.insert(FireSecondary);
+            }
+            (false, true) => {
+                ship.remove::<FireSecondary>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -2,6 +2,8 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
+use crate::GameState;
+
 #[derive(Component)]
 pub struct Energy {
     pub max: f32,
@@ -35,6 +37,15 @@ impl Energy {
         }
     }
 
+    /// Drains `amount` if enough energy is left, returns whether it did.
+    pub fn consume(&mut self, amount: f32) -> bool {
+        if self.current < amount {
+            return false;
+        }
+        self.current -= amount;
+        true
+    }
+
     #[inline(always)]
     pub fn current(&self) -> f32 {
         self.current
@@ -57,7 +68,7 @@ pub struct EnergyPlugin;
 
 impl Plugin for EnergyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(tick);
+        app.add_system_set(SystemSet::on_update(GameState::Playing).with_system(tick));
     }
 }
 
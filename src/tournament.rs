@@ -0,0 +1,439 @@
+//! Headless bot-vs-bot matches between every pair of ship class and weapon
+//! for balance testing.
+//!
+//! Run with `cargo run --release -- -u t -P t --tournament report.csv`.
+//! Besides the win rates in `report.csv` the average energy of every loadout
+//! over time is written to `report-energy.csv`.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use bevy::{ecs::system::SystemState, prelude::*, utils::HashMap};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    bot::{Bot, BotPlugin, BotSettings},
+    components::{RoundEntity, Team},
+    events::GameEventsPlugin,
+    mode::MatchConfig,
+    player::Player,
+    ship::{
+        energy::EnergyPlugin, spawn_ship, Energy, FireMode, ShipConfig, ShipInput, ShipPlugin,
+        WeaponMount,
+    },
+    weapon::{ProjectilePool, SpreadRng, WeaponDefinition, WeaponGroup, WeaponPlugin},
+    GameState,
+};
+
+const FPS: u32 = 60;
+/// Matches without a kill by then are a draw.
+const MAX_MATCH_SECS: u32 = 60;
+const START_DISTANCE: f32 = 800.0;
+/// Frames to wait for the weapon definitions before giving up.
+const LOAD_FRAMES: u32 = 600;
+
+const WEAPONS: [&str; 7] = [
+    "weapons/blaster.weapon.ron",
+    "weapons/wing_gun.weapon.ron",
+    "weapons/shotgun.weapon.ron",
+    "weapons/burst_rifle.weapon.ron",
+    "weapons/missile.weapon.ron",
+    "weapons/laser.weapon.ron",
+    "weapons/mine.weapon.ron",
+];
+
+struct ShipClass {
+    name: &'static str,
+    acceleration: f32,
+    ship_sprite: &'static str,
+    hull: f32,
+}
+
+const CLASSES: [ShipClass; 3] = [
+    ShipClass {
+        name: "Fighter",
+        acceleration: 140.0,
+        ship_sprite: "ships/1.png",
+        hull: 20.0,
+    },
+    ShipClass {
+        name: "Interceptor",
+        acceleration: 180.0,
+        ship_sprite: "ships/2.png",
+        hull: 14.0,
+    },
+    ShipClass {
+        name: "Tank",
+        acceleration: 100.0,
+        ship_sprite: "ships/4.png",
+        hull: 35.0,
+    },
+];
+
+#[derive(Clone, Copy)]
+struct Loadout {
+    class: &'static ShipClass,
+    weapon: &'static str,
+}
+
+impl Loadout {
+    fn all() -> Vec<Loadout> {
+        CLASSES
+            .iter()
+            .flat_map(|class| WEAPONS.iter().map(move |weapon| Loadout { class, weapon }))
+            .collect()
+    }
+
+    fn weapon_name(&self) -> &'static str {
+        let file = self.weapon.rsplit('/').next().unwrap_or(self.weapon);
+        file.trim_end_matches(".weapon.ron")
+    }
+
+    fn name(&self) -> String {
+        format!("{} {}", self.class.name, self.weapon_name())
+    }
+}
+
+/// Totals of one loadout over all its matches.
+#[derive(Default)]
+struct Stats {
+    matches: u32,
+    wins: u32,
+    losses: u32,
+    /// Seconds from the start of each won match to the kill.
+    kill_times: Vec<f32>,
+    /// Sum and number of energy samples per second of match time.
+    energy: Vec<(f32, u32)>,
+}
+
+impl Stats {
+    fn add_energy(&mut self, second: usize, energy: f32) {
+        if self.energy.len() <= second {
+            self.energy.resize(second + 1, (0.0, 0));
+        }
+        self.energy[second].0 += energy;
+        self.energy[second].1 += 1;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct MatchResult {
+    /// Index of the winning side, `None` for a draw.
+    winner: Option<usize>,
+    seconds: f32,
+    /// Energy of both sides at every full second while alive.
+    energy: [Vec<f32>; 2],
+}
+
+pub fn run(report: &Path, matches: u32, bots: BotSettings) {
+    let mut app = build_app();
+    // Kept so the definitions stay loaded for all matches.
+    let _definitions = load_definitions(&mut app);
+
+    let loadouts = Loadout::all();
+    let mut stats: HashMap<String, Stats> = HashMap::default();
+    let mut seed = bots.seed;
+    let started = Instant::now();
+    for (i, a) in loadouts.iter().enumerate() {
+        // Mirror matches would count twice for the same loadout.
+        for b in &loadouts[i + 1..] {
+            for n in 0..matches {
+                // Sides swap every match so neither loadout keeps the bot
+                // preset of one slot.
+                let sides = if n % 2 == 0 { [*a, *b] } else { [*b, *a] };
+                let result = play(&mut app, sides, &bots, seed);
+                seed += 1;
+                for (side, loadout) in sides.iter().enumerate() {
+                    let stats = stats.entry(loadout.name()).or_default();
+                    stats.matches += 1;
+                    match result.winner {
+                        Some(winner) if winner == side => {
+                            stats.wins += 1;
+                            stats.kill_times.push(result.seconds);
+                        }
+                        Some(_) => stats.losses += 1,
+                        None => {}
+                    }
+                    for (second, energy) in result.energy[side].iter().enumerate() {
+                        stats.add_energy(second, *energy);
+                    }
+                }
+            }
+        }
+        println!(
+            "{}/{} loadouts done after {:?}",
+            i + 1,
+            loadouts.len(),
+            started.elapsed()
+        );
+    }
+
+    let energy_report = energy_path(report);
+    if let Err(e) = write_report(report, &loadouts, &stats)
+        .and_then(|_| write_energy(&energy_report, &loadouts, &stats))
+    {
+        eprintln!("Could not write the tournament report: {}", e);
+        return;
+    }
+    println!("Wrote {} and {}", report.display(), energy_report.display());
+}
+
+fn build_app() -> App {
+    let mut app = App::new();
+    app.add_plugin(CorePlugin)
+        .init_resource::<Time>()
+        .add_system_to_stage(CoreStage::First, step_time)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(AssetPlugin)
+        .add_asset::<Image>()
+        .add_asset::<TextureAtlas>()
+        .add_state(GameState::Playing)
+        .insert_resource(MatchConfig::default())
+        .add_plugin(GameEventsPlugin)
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+        .add_plugin(ShipPlugin)
+        .add_plugin(EnergyPlugin)
+        .add_plugin(WeaponPlugin)
+        .add_plugin(BotPlugin);
+
+    // Physics must not depend on how fast the machine runs the matches.
+    let mut rapier = app.world.resource_mut::<RapierConfiguration>();
+    rapier.gravity = Vec2::ZERO;
+    rapier.timestep_mode = TimestepMode::Fixed {
+        dt: 1.0 / FPS as f32,
+        substeps: 1,
+    };
+    app
+}
+
+/// Advances the clock by exactly one frame per update.
+fn step_time(mut time: ResMut<Time>, mut now: Local<Option<Instant>>) {
+    let next = match *now {
+        Some(last) => last + Duration::from_secs(1) / FPS,
+        None => Instant::now(),
+    };
+    time.update_with_instant(next);
+    *now = Some(next);
+}
+
+/// Loads every weapon up front so no match starts with unarmed ships.
+fn load_definitions(app: &mut App) -> Vec<Handle<WeaponDefinition>> {
+    let handles: Vec<Handle<WeaponDefinition>> = {
+        let asset_server = app.world.resource::<AssetServer>();
+        WEAPONS
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect()
+    };
+    for _ in 0..LOAD_FRAMES {
+        app.update();
+        let definitions = app.world.resource::<Assets<WeaponDefinition>>();
+        if handles.iter().all(|h| definitions.contains(h)) {
+            return handles;
+        }
+    }
+    panic!("Weapon definitions did not load");
+}
+
+fn ship_config(loadout: Loadout, team: Team) -> ShipConfig<'static> {
+    ShipConfig {
+        name: loadout.class.name,
+        acceleration: loadout.class.acceleration,
+        ship_sprite: loadout.class.ship_sprite,
+        exhaust_sprite_sheet: "ships/exhaust/exhaust1.png",
+        max_energy: 100.0,
+        energy_decay: 0.8,
+        energy_start_value: 100.0,
+        hull: loadout.class.hull,
+        weapons: vec![WeaponMount {
+            definition: loadout.weapon,
+            offset: Vec2::ZERO,
+            group: WeaponGroup::Primary,
+        }],
+        fire_mode: FireMode::Together,
+        team,
+        tint: team.color(),
+    }
+}
+
+/// Plays one match between the two loadouts, facing each other.
+fn play(app: &mut App, loadouts: [Loadout; 2], bots: &BotSettings, seed: u64) -> MatchResult {
+    app.world.insert_resource(SpreadRng::seeded(seed));
+    let ships: Vec<Entity> = loadouts
+        .iter()
+        .enumerate()
+        .map(|(handle, loadout)| spawn(app, *loadout, handle, bots, seed))
+        .collect();
+
+    let mut energy = [Vec::new(), Vec::new()];
+    let mut frame = 0;
+    let winner = loop {
+        app.update();
+        frame += 1;
+
+        let alive: Vec<bool> = ships
+            .iter()
+            .map(|ship| app.world.get_entity(*ship).is_some())
+            .collect();
+        match alive.as_slice() {
+            [true, false] => break Some(0),
+            [false, true] => break Some(1),
+            [false, false] => break None,
+            _ => {}
+        }
+        if frame % FPS == 0 {
+            for (side, ship) in ships.iter().enumerate() {
+                if let Some(e) = app.world.get::<Energy>(*ship) {
+                    energy[side].push(e.current);
+                }
+            }
+        }
+        if frame >= MAX_MATCH_SECS * FPS {
+            break None;
+        }
+    };
+
+    cleanup(app);
+    MatchResult {
+        winner,
+        seconds: frame as f32 / FPS as f32,
+        energy,
+    }
+}
+
+fn spawn(app: &mut App, loadout: Loadout, handle: usize, bots: &BotSettings, seed: u64) -> Entity {
+    let team = Team(handle);
+    let x = START_DISTANCE / 2.0 * if handle == 0 { -1.0 } else { 1.0 };
+    // Both ships start pointing at each other.
+    let rotation = Quat::from_rotation_z(x.signum() * std::f32::consts::FRAC_PI_2);
+
+    let mut state: SystemState<(Commands, Res<AssetServer>, ResMut<Assets<TextureAtlas>>)> =
+        SystemState::new(&mut app.world);
+    let (mut commands, asset_server, mut textures) = state.get_mut(&mut app.world);
+    let ship = spawn_ship(
+        ship_config(loadout, team),
+        &mut commands,
+        &asset_server,
+        &mut textures,
+    );
+    commands
+        .entity(ship)
+        .insert(Transform::from_xyz(x, 0.0, 0.0).with_rotation(rotation))
+        .insert(Player { handle })
+        .insert(Bot::new(bots.preset(handle), seed * 2 + handle as u64))
+        .insert(ShipInput::default());
+    state.apply(&mut app.world);
+    ship
+}
+
+/// Despawns what is left of the last match.
+fn cleanup(app: &mut App) {
+    let entities: Vec<Entity> = app
+        .world
+        .query_filtered::<Entity, With<RoundEntity>>()
+        .iter(&app.world)
+        .collect();
+    for e in entities {
+        // Children go with their parents.
+        if let Some(entity) = app.world.get_entity_mut(e) {
+            entity.despawn_recursive();
+        }
+    }
+    app.world.resource_mut::<ProjectilePool>().clear();
+}
+
+fn energy_path(report: &Path) -> PathBuf {
+    let stem = report.file_stem().unwrap_or_default().to_string_lossy();
+    report.with_file_name(format!("{}-energy.csv", stem))
+}
+
+fn write_report(
+    path: &Path,
+    loadouts: &[Loadout],
+    stats: &HashMap<String, Stats>,
+) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(
+        file,
+        "class,weapon,matches,wins,losses,draws,win_rate,avg_time_to_kill"
+    )?;
+    // Loadouts without matches, e.g. with `--matches 0`, get a row of zeroes.
+    let empty = Stats::default();
+    for loadout in loadouts {
+        let s = stats.get(&loadout.name()).unwrap_or(&empty);
+        let draws = s.matches - s.wins - s.losses;
+        let win_rate = s.wins as f32 / s.matches.max(1) as f32;
+        let time_to_kill = if s.kill_times.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "{:.2}",
+                s.kill_times.iter().sum::<f32>() / s.kill_times.len() as f32
+            )
+        };
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{:.3},{}",
+            loadout.class.name,
+            loadout.weapon_name(),
+            s.matches,
+            s.wins,
+            s.losses,
+            draws,
+            win_rate,
+            time_to_kill
+        )?;
+    }
+    file.flush()
+}
+
+/// Average energy of the ships still alive at every second of match time.
+fn write_energy(
+    path: &Path,
+    loadouts: &[Loadout],
+    stats: &HashMap<String, Stats>,
+) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "class,weapon,second,ships,avg_energy")?;
+    for loadout in loadouts {
+        let energy = stats
+            .get(&loadout.name())
+            .map(|s| s.energy.as_slice())
+            .unwrap_or_default();
+        for (second, (sum, count)) in energy.iter().enumerate() {
+            writeln!(
+                file,
+                "{},{},{},{},{:.2}",
+                loadout.class.name,
+                loadout.weapon_name(),
+                second + 1,
+                count,
+                sum / *count as f32
+            )?;
+        }
+    }
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_with_the_same_seed_play_out_the_same() {
+        let mut app = build_app();
+        let _definitions = load_definitions(&mut app);
+        let bots = BotSettings::default();
+        let loadouts = Loadout::all();
+        let pairing = [loadouts[0], loadouts[loadouts.len() - 1]];
+
+        let first = play(&mut app, pairing, &bots, 7);
+        let second = play(&mut app, pairing, &bots, 7);
+        assert_eq!(first, second);
+    }
+}
@@ -1,10 +1,29 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy_ggrs::RollbackIdProvider;
 use bevy_rapier2d::prelude::*;
 use getset::MutGetters;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use crate::{components::Hp, utils::direction};
+use crate::{
+    components::{Hp, Team},
+    ship::{self, Energy, Ship},
+    utils::direction,
+    GameState,
+};
+
+pub mod damage;
+pub mod definition;
+pub mod pool;
+pub mod upgrade;
+pub use damage::{DamageEvent, DamageRules, Owner};
+pub use definition::{ProjectileKind, WeaponDefinition};
+pub use pool::ProjectilePool;
+pub use upgrade::{Upgrade, UpgradeLevels, WeaponUpgrades};
+
+use self::definition::WeaponDefinitionLoader;
 
 pub struct FireWeaponEvent(pub Entity);
 
@@ -14,6 +33,14 @@ pub struct Weapon;
 #[derive(Component, Debug)]
 pub struct FireWeapon;
 
+/// Which trigger of the ship fires a weapon.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeaponGroup {
+    #[default]
+    Primary,
+    Secondary,
+}
+
 #[derive(Component, MutGetters, Debug)]
 #[getset(get_mut = "pub")]
 pub struct FireRate {
@@ -23,9 +50,24 @@ pub struct FireRate {
 
 impl FireRate {
     pub fn new(rate: f32) -> Self {
-        let timer = Timer::new(Duration::from_millis((1000.0 / rate) as u64), true);
+        let timer = Timer::new(Self::interval(rate), true);
         Self { rate, timer }
     }
+
+    /// Changes the rate without restarting the shot being reloaded.
+    pub fn set_rate(&mut self, rate: f32) {
+        let interval = Self::interval(rate);
+        let elapsed = self.timer.elapsed().min(interval);
+        self.rate = rate;
+        self.timer.set_duration(interval);
+        self.timer.set_elapsed(elapsed);
+    }
+
+    /// Upgrades can push rates past [`MAX_FIRE_RATE`](definition::MAX_FIRE_RATE),
+    /// a repeating timer still needs a length.
+    fn interval(rate: f32) -> Duration {
+        Duration::from_millis(((1000.0 / rate) as u64).max(1))
+    }
 }
 
 impl Default for FireRate {
@@ -43,12 +85,82 @@ pub struct ProjectileSpeed(pub f32);
 #[derive(Component, Default)]
 pub struct ProjectileLifetime(pub f32);
 
+/// Upgrade levels of the ship last applied to a weapon's stats.
+#[derive(Component, Default)]
+pub struct AppliedUpgrades(pub UpgradeLevels);
+
+/// Projectiles per shot.
+#[derive(Component)]
+pub struct ProjectileCount(pub u32);
+
+impl Default for ProjectileCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Heat building up with every shot. A weapon reaching `max` overheats and
+/// can't fire until it has cooled down completely.
+#[derive(Component, Debug, Default)]
+pub struct Heat {
+    pub current: f32,
+    pub max: f32,
+    pub per_shot: f32,
+    pub cooling: f32,
+    pub overheated: bool,
+}
+
+impl Heat {
+    pub fn new(max: f32, per_shot: f32, cooling: f32) -> Self {
+        Self {
+            max,
+            per_shot,
+            cooling,
+            ..Default::default()
+        }
+    }
+
+    pub fn increase(&mut self, amount: f32) {
+        if self.per_shot <= 0.0 {
+            return;
+        }
+        self.current += amount;
+        if self.current >= self.max {
+            self.current = self.max;
+            self.overheated = true;
+        }
+    }
+
+    pub fn cool(&mut self, seconds: f32) {
+        self.current -= self.cooling * seconds;
+        if self.current <= 0.0 {
+            self.current = 0.0;
+            self.overheated = false;
+        }
+    }
+
+    #[inline(always)]
+    pub fn current_percentage(&self) -> f32 {
+        if self.max > 0.0 {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct WeaponBundle {
     pub weapon: Weapon,
+    pub group: WeaponGroup,
+    pub definition: Handle<WeaponDefinition>,
     pub fire_rate: FireRate,
     pub damage: Damage,
     pub speed: ProjectileSpeed,
+    pub lifetime: ProjectileLifetime,
+    pub count: ProjectileCount,
+    pub heat: Heat,
+    pub upgrades: AppliedUpgrades,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
 }
@@ -58,28 +170,72 @@ impl Default for WeaponBundle {
         let fire_rate = FireRate::default();
         Self {
             weapon: Weapon,
+            group: WeaponGroup::default(),
+            definition: Handle::default(),
             fire_rate,
             damage: Damage::default(),
             speed: ProjectileSpeed::default(),
+            lifetime: ProjectileLifetime::default(),
+            count: ProjectileCount::default(),
+            heat: Heat::default(),
+            upgrades: AppliedUpgrades::default(),
             transform: Transform::default(),
             global_transform: GlobalTransform::default(),
         }
     }
 }
 
+/// Randomness of the weapon spread, seeded to make simulations repeatable.
+pub struct SpreadRng(pub StdRng);
+
+impl Default for SpreadRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
+
+impl SpreadRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Smallest angle in degrees between two pellets of a shot.
+const MIN_PELLET_ANGLE: f32 = 5.0;
+
 pub struct WeaponPlugin;
 
 impl Plugin for WeaponPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<FireWeaponEvent>()
-            .add_system(fire_weapons)
-            .add_system(collide)
-            .add_system(projectile_life_time);
+        app.add_asset::<WeaponDefinition>()
+            .init_asset_loader::<WeaponDefinitionLoader>()
+            .add_event::<FireWeaponEvent>()
+            .add_event::<DamageEvent>()
+            .init_resource::<DamageRules>()
+            .init_resource::<ProjectilePool>()
+            .init_resource::<WeaponUpgrades>()
+            .init_resource::<SpreadRng>()
+            .add_system(apply_definitions)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(fire_weapons.after(apply_definitions))
+                    .with_system(fire_bursts.after(fire_weapons))
+                    // Beam sprites are added to weapons of ships that may be
+                    // despawned this frame.
+                    .with_system(fire_beams.before(ship::destroy))
+                    .with_system(steer_homing)
+                    .with_system(detonate_mines)
+                    .with_system(cool_weapons)
+                    .with_system(collide)
+                    .with_system(projectile_life_time),
+            )
+            .add_system(upgrade::reset_destroyed)
+            .add_system_to_stage(CoreStage::PostUpdate, damage::apply_damage);
     }
 }
 
 #[derive(Component)]
-struct Projectile;
+pub struct Projectile;
 
 #[derive(Component)]
 struct ProjectileDamage(pub f32);
@@ -87,54 +243,620 @@ struct ProjectileDamage(pub f32);
 #[derive(Component)]
 struct ProjectileLifeTimer(Timer);
 
+#[derive(Component)]
+struct Knockback(f32);
+
+/// Copies the stats of a loaded [`WeaponDefinition`] onto the weapons using it,
+/// scaled by the upgrade levels of the ship carrying them.
+fn apply_definitions(
+    mut events: EventReader<AssetEvent<WeaponDefinition>>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    upgrades: Res<WeaponUpgrades>,
+    mut weapons: Query<(
+        &Parent,
+        &Handle<WeaponDefinition>,
+        ChangeTrackers<Handle<WeaponDefinition>>,
+        &mut FireRate,
+        &mut Damage,
+        &mut ProjectileSpeed,
+        &mut ProjectileLifetime,
+        &mut ProjectileCount,
+        &mut Heat,
+        &mut AppliedUpgrades,
+    )>,
+) {
+    let changed: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect();
+
+    for (
+        parent,
+        handle,
+        tracker,
+        mut fire_rate,
+        mut damage,
+        mut speed,
+        mut lifetime,
+        mut count,
+        mut heat,
+        mut applied,
+    ) in weapons.iter_mut()
+    {
+        let definition_changed = tracker.is_added() || changed.contains(&handle);
+        // Only the weapons of ships whose levels changed are touched, the
+        // others keep their fire timers.
+        let levels = upgrades.get(**parent);
+        if !definition_changed && (!upgrades.is_changed() || applied.0 == levels) {
+            continue;
+        }
+        if let Some(definition) = definitions.get(handle) {
+            applied.0 = levels;
+            fire_rate.set_rate(definition.fire_rate * levels.fire_rate_factor());
+            damage.0 = definition.damage * levels.damage_factor();
+            speed.0 = definition.projectile_speed * levels.projectile_speed_factor();
+            lifetime.0 = definition.projectile_lifetime;
+            count.0 = definition.pellets.max(1) + levels.extra_projectiles();
+            if definition_changed {
+                *heat = Heat::new(
+                    definition.max_heat,
+                    definition.heat_per_shot,
+                    definition.cooling_rate,
+                );
+            }
+        }
+    }
+}
+
+/// Everything needed to spawn the projectiles of a single shot.
+struct Shot<'a> {
+    definition: &'a WeaponDefinition,
+    muzzle: Transform,
+    launcher: Entity,
+    launcher_team: Option<Team>,
+    /// Collision groups of the launcher, left out by its projectiles.
+    launcher_groups: u32,
+    launcher_velocity: Vec2,
+    damage: f32,
+    speed: f32,
+    lifetime: f32,
+    pellets: u32,
+}
+
+/// Remaining shots of a burst that is currently being fired.
+#[derive(Component)]
+struct BurstFire {
+    remaining: u32,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct Homing {
+    turn_rate: f32,
+    range: f32,
+}
+
+/// Sprite rendering the beam of a weapon.
+#[derive(Component)]
+struct BeamSprite(Entity);
+
+#[derive(Component)]
+struct Mine {
+    arm_timer: Timer,
+    trigger_radius: f32,
+    blast_radius: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn fire_weapons(
     mut commands: Commands,
     mut weapons: Query<(
+        Entity,
         &Parent,
         &Weapon,
+        &Handle<WeaponDefinition>,
         &Damage,
         &ProjectileSpeed,
+        &ProjectileLifetime,
+        &ProjectileCount,
         &GlobalTransform,
         Option<&FireWeapon>,
         &mut FireRate,
+        &mut Heat,
     )>,
-    velocites: Query<&Velocity>,
+    mut ships: Query<(
+        &Velocity,
+        Option<&mut Energy>,
+        Option<&Team>,
+        Option<&CollisionGroups>,
+    )>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    asset_server: Res<AssetServer>,
+    mut pool: ResMut<ProjectilePool>,
+    mut rip: Option<ResMut<RollbackIdProvider>>,
+    mut rng: ResMut<SpreadRng>,
     time: Res<Time>,
 ) {
     let delta = time.delta();
-    for (parent, _, damage, speed, global_transform, fire, mut fire_rate) in weapons.iter_mut() {
+    for (
+        e,
+        parent,
+        _,
+        handle,
+        damage,
+        speed,
+        lifetime,
+        count,
+        global_transform,
+        fire,
+        mut fire_rate,
+        mut heat,
+    ) in weapons.iter_mut()
+    {
         let timer = fire_rate.timer_mut();
         timer.tick(delta);
-        if timer.just_finished() && fire.is_some() {
-            info!("pew pew pew");
-            let mut transform = global_transform.compute_transform();
-            let (x, y) = direction(&transform);
-            let v = velocites.get(**parent).unwrap();
-
-            info!("{} {}", x, y);
-            info!("{:?}", v);
-
-            // TODO: hack, set weapon output position
-            transform.translation += Transform::from_xyz(x, y, 0.0).translation * 100.0;
-
-            commands
-                .spawn_bundle(SpriteBundle {
-                    transform,
-                    ..Default::default()
-                })
-                .insert(Projectile)
-                .insert(ProjectileDamage(damage.0))
-                .insert(ProjectileLifeTimer(Timer::new(
-                    Duration::from_millis(1500),
-                    false,
-                )))
-                .insert(ActiveEvents::COLLISION_EVENTS)
-                .insert(RigidBody::Dynamic)
-                .insert(Velocity {
-                    linvel: v.linvel + Vec2::new(x, y) * speed.0,
-                    angvel: 0.0,
-                })
-                .insert(Collider::ball(1.0));
+        if !timer.just_finished() || fire.is_none() || heat.overheated {
+            continue;
+        }
+        let definition = match definitions.get(handle) {
+            Some(definition) => definition,
+            None => continue,
+        };
+        if let ProjectileKind::Beam { .. } = definition.projectile {
+            continue;
+        }
+        let (v, energy, team, groups) = ships.get_mut(**parent).unwrap();
+        if let Some(mut energy) = energy {
+            if !energy.consume(definition.energy_cost) {
+                continue;
+            }
+        }
+
+        info!("pew pew pew");
+        let per_shot = heat.per_shot;
+        heat.increase(per_shot);
+        spawn_shot(
+            &mut commands,
+            &asset_server,
+            &mut pool,
+            rip.as_deref_mut(),
+            &mut rng.0,
+            Shot {
+                definition,
+                muzzle: global_transform.compute_transform(),
+                launcher: **parent,
+                launcher_team: team.copied(),
+                launcher_groups: groups.map_or(0, |g| g.memberships),
+                launcher_velocity: v.linvel,
+                damage: damage.0,
+                speed: speed.0,
+                lifetime: lifetime.0,
+                pellets: count.0,
+            },
+        );
+
+        if let Some(burst) = definition.burst {
+            if burst.count > 1 {
+                commands.entity(e).insert(BurstFire {
+                    remaining: burst.count - 1,
+                    timer: Timer::from_seconds(burst.interval, true),
+                });
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn fire_bursts(
+    mut commands: Commands,
+    mut weapons: Query<(
+        Entity,
+        &Parent,
+        &Handle<WeaponDefinition>,
+        &Damage,
+        &ProjectileSpeed,
+        &ProjectileLifetime,
+        &ProjectileCount,
+        &GlobalTransform,
+        &mut BurstFire,
+        &mut Heat,
+    )>,
+    mut ships: Query<(
+        &Velocity,
+        Option<&mut Energy>,
+        Option<&Team>,
+        Option<&CollisionGroups>,
+    )>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    asset_server: Res<AssetServer>,
+    mut pool: ResMut<ProjectilePool>,
+    mut rip: Option<ResMut<RollbackIdProvider>>,
+    mut rng: ResMut<SpreadRng>,
+    time: Res<Time>,
+) {
+    for (
+        e,
+        parent,
+        handle,
+        damage,
+        speed,
+        lifetime,
+        count,
+        global_transform,
+        mut burst,
+        mut heat,
+    ) in weapons.iter_mut()
+    {
+        burst.timer.tick(time.delta());
+        if !burst.timer.just_finished() {
+            continue;
+        }
+        if let Some(definition) = definitions.get(handle) {
+            let (v, energy, team, groups) = ships.get_mut(**parent).unwrap();
+            // Overheating or running out of energy cuts the burst short.
+            // Energy is only paid for shots that are fired.
+            let cut_short = heat.overheated
+                || energy.map_or(false, |mut energy| !energy.consume(definition.energy_cost));
+            if cut_short {
+                commands.entity(e).remove::<BurstFire>();
+                continue;
+            }
+            let per_shot = heat.per_shot;
+            heat.increase(per_shot);
+            spawn_shot(
+                &mut commands,
+                &asset_server,
+                &mut pool,
+                rip.as_deref_mut(),
+                &mut rng.0,
+                Shot {
+                    definition,
+                    muzzle: global_transform.compute_transform(),
+                    launcher: **parent,
+                    launcher_team: team.copied(),
+                    launcher_groups: groups.map_or(0, |g| g.memberships),
+                    launcher_velocity: v.linvel,
+                    damage: damage.0,
+                    speed: speed.0,
+                    lifetime: lifetime.0,
+                    pellets: count.0,
+                },
+            );
+        }
+        burst.remaining -= 1;
+        if burst.remaining == 0 {
+            commands.entity(e).remove::<BurstFire>();
+        }
+    }
+}
+
+fn spawn_shot(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    pool: &mut ProjectilePool,
+    mut rip: Option<&mut RollbackIdProvider>,
+    rng: &mut StdRng,
+    shot: Shot,
+) {
+    let definition = shot.definition;
+    let mut muzzle = shot.muzzle;
+    muzzle.translation += muzzle.rotation * definition.muzzle_offset();
+
+    let pellets = shot.pellets.max(1);
+    // Extra pellets of precise weapons still fan out instead of stacking up.
+    let spread = definition
+        .spread
+        .max(MIN_PELLET_ANGLE * (pellets - 1) as f32)
+        .to_radians();
+    for i in 0..pellets {
+        let mut transform = muzzle;
+        let angle = if pellets > 1 {
+            -spread / 2.0 + spread * i as f32 / (pellets - 1) as f32
+        } else if spread > 0.0 {
+            rng.gen_range(-spread / 2.0..=spread / 2.0)
+        } else {
+            0.0
+        };
+        transform.rotate_z(angle);
+        let (x, y) = direction(&transform);
+
+        let radius = definition.projectile_radius;
+        let mut projectile = pool.acquire(commands, rip.as_deref_mut());
+        projectile.insert_bundle(SpriteBundle {
+            transform,
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(2.0 * radius)),
+                ..Default::default()
+            },
+            texture: definition
+                .projectile_sprite
+                .as_ref()
+                .map(|path| asset_server.load(path.as_str()))
+                .unwrap_or_default(),
+            ..Default::default()
+        });
+        projectile
+            .insert(Projectile)
+            .insert(Owner(shot.launcher))
+            .insert(Knockback(definition.knockback))
+            .insert(ProjectileLifeTimer(Timer::new(
+                Duration::from_secs_f32(shot.lifetime),
+                false,
+            )))
+            .insert(Collider::ball(radius))
+            .insert(CollisionGroups::new(u32::MAX, !shot.launcher_groups));
+        if let Some(team) = shot.launcher_team {
+            projectile.insert(team);
+        }
+
+        match definition.projectile {
+            ProjectileKind::Bullet | ProjectileKind::Homing { .. } => {
+                projectile
+                    .insert(ProjectileDamage(shot.damage))
+                    .insert(ActiveEvents::COLLISION_EVENTS)
+                    .insert(RigidBody::Dynamic)
+                    .insert(Velocity {
+                        linvel: shot.launcher_velocity + Vec2::new(x, y) * shot.speed,
+                        angvel: 0.0,
+                    });
+            }
+            ProjectileKind::Mine { .. } => {
+                projectile.insert(RigidBody::Fixed).insert(Sensor);
+            }
+            ProjectileKind::Beam { .. } => unreachable!("beams are not spawned as projectiles"),
+        }
+
+        match definition.projectile {
+            ProjectileKind::Bullet => {}
+            ProjectileKind::Homing { turn_rate, range } => {
+                projectile.insert(Homing {
+                    turn_rate: turn_rate.to_radians(),
+                    range,
+                });
+            }
+            ProjectileKind::Mine {
+                arm_delay,
+                trigger_radius,
+                blast_radius,
+            } => {
+                projectile.insert(Mine {
+                    arm_timer: Timer::from_seconds(arm_delay, false),
+                    trigger_radius,
+                    blast_radius,
+                });
+                // Blast damage is stored on the mine itself, it never hits by contact.
+                projectile.insert(ProjectileDamage(shot.damage));
+            }
+            ProjectileKind::Beam { .. } => {}
+        }
+    }
+}
+
+/// Casts the rays of beam weapons and damages whatever they hit first.
+#[allow(clippy::too_many_arguments)]
+fn fire_beams(
+    mut commands: Commands,
+    mut weapons: Query<(
+        Entity,
+        &Parent,
+        &Handle<WeaponDefinition>,
+        &Damage,
+        &GlobalTransform,
+        Option<&FireWeapon>,
+        Option<&BeamSprite>,
+        &mut Heat,
+    )>,
+    mut sprites: Query<(&mut Transform, &mut Sprite, &mut Visibility), Without<Hp>>,
+    mut energies: Query<&mut Energy>,
+    targets: Query<(Option<&Team>, &Transform), With<Hp>>,
+    teams: Query<&Team>,
+    projectiles: Query<&Projectile>,
+    sensors: Query<(), With<Sensor>>,
+    definitions: Res<Assets<WeaponDefinition>>,
+    rules: Res<DamageRules>,
+    rapier_context: Res<RapierContext>,
+    mut damage_events: EventWriter<DamageEvent>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (e, parent, handle, damage, global_transform, fire, beam_sprite, mut heat) in
+        weapons.iter_mut()
+    {
+        let definition = match definitions.get(handle) {
+            Some(definition) => definition,
+            None => continue,
+        };
+        let (range, width) = match definition.projectile {
+            ProjectileKind::Beam { range, width } => (range, width),
+            _ => continue,
+        };
+
+        let firing = fire.is_some()
+            && !heat.overheated
+            && energies.get_mut(**parent).map_or(true, |mut energy| {
+                energy.consume(definition.energy_cost * dt)
+            });
+
+        if !firing {
+            if let Some(beam_sprite) = beam_sprite {
+                if let Ok((_, _, mut visibility)) = sprites.get_mut(beam_sprite.0) {
+                    visibility.is_visible = false;
+                }
+            }
+            continue;
+        }
+
+        let per_second = heat.per_shot;
+        heat.increase(per_second * dt);
+
+        let mut muzzle = global_transform.compute_transform();
+        muzzle.translation += muzzle.rotation * definition.muzzle_offset();
+        let (x, y) = direction(&muzzle);
+        // Beams pass through projectiles and sensors that can't be damaged,
+        // like the capture zone, but stop at orbs.
+        let blocks_beam = |entity| {
+            !projectiles.contains(entity) && (!sensors.contains(entity) || targets.contains(entity))
+        };
+        let filter = QueryFilter::new()
+            .exclude_rigid_body(**parent)
+            .predicate(&blocks_beam);
+        let length = match rapier_context.cast_ray(
+            muzzle.translation.truncate(),
+            Vec2::new(x, y),
+            range,
+            true,
+            filter,
+        ) {
+            Some((hit, toi)) => {
+                if let Ok((target_team, t)) = targets.get(hit) {
+                    let team = teams.get(**parent).ok();
+                    if rules.can_damage(Some(**parent), team, hit, target_team) {
+                        damage_events.send(DamageEvent {
+                            target: hit,
+                            amount: damage.0 * dt,
+                            source: Some(**parent),
+                            position: t.translation.truncate(),
+                        });
+                    }
+                }
+                toi
+            }
+            None => range,
+        };
+
+        // The sprite is a child of the weapon and goes with it when the ship is destroyed.
+        let beam_transform = Transform::from_translation(definition.muzzle_offset() + Vec3::Z);
+        match beam_sprite.and_then(|b| sprites.get_mut(b.0).ok()) {
+            Some((mut transform, mut sprite, mut visibility)) => {
+                *transform = beam_transform;
+                sprite.custom_size = Some(Vec2::new(width, length));
+                visibility.is_visible = true;
+            }
+            None => {
+                let sprite = commands
+                    .spawn_bundle(SpriteBundle {
+                        transform: beam_transform,
+                        sprite: Sprite {
+                            color: Color::CYAN,
+                            custom_size: Some(Vec2::new(width, length)),
+                            anchor: Anchor::BottomCenter,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .id();
+                commands
+                    .entity(e)
+                    .insert(BeamSprite(sprite))
+                    .add_child(sprite);
+            }
+        }
+    }
+}
+
+/// Turns homing projectiles towards the nearest ship they may damage.
+#[allow(clippy::type_complexity)]
+fn steer_homing(
+    mut projectiles: Query<
+        (
+            &Homing,
+            &Owner,
+            Option<&Team>,
+            &mut Transform,
+            &mut Velocity,
+        ),
+        Without<Ship>,
+    >,
+    ships: Query<(Entity, Option<&Team>, &Transform), With<Ship>>,
+    rules: Res<DamageRules>,
+    time: Res<Time>,
+) {
+    for (homing, owner, team, mut transform, mut velocity) in projectiles.iter_mut() {
+        let position = transform.translation.truncate();
+        let target = ships
+            .iter()
+            .filter(|(e, target_team, _)| rules.can_damage(Some(owner.0), team, *e, *target_team))
+            .map(|(_, _, t)| t.translation.truncate() - position)
+            .filter(|offset| offset.length() < homing.range)
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+        let target = match target {
+            Some(target) => target,
+            None => continue,
+        };
+        let speed = velocity.linvel.length();
+        if speed == 0.0 {
+            continue;
+        }
+        let angle = velocity.linvel.angle_between(target);
+        let max_turn = homing.turn_rate * time.delta_seconds();
+        let turn = angle.clamp(-max_turn, max_turn);
+        velocity.linvel = Vec2::from_angle(turn).rotate(velocity.linvel);
+        transform.rotate_z(turn);
+    }
+}
+
+/// Arms mines and detonates them once a ship they can damage is within their
+/// trigger radius.
+#[allow(clippy::too_many_arguments)]
+fn detonate_mines(
+    mut commands: Commands,
+    mut mines: Query<(
+        Entity,
+        &mut Mine,
+        &ProjectileDamage,
+        &Owner,
+        Option<&Team>,
+        &Transform,
+    )>,
+    ships: Query<(Entity, Option<&Team>, &Transform), With<Ship>>,
+    targets: Query<(Entity, Option<&Team>, &Transform), With<Hp>>,
+    rules: Res<DamageRules>,
+    mut pool: ResMut<ProjectilePool>,
+    mut damage_events: EventWriter<DamageEvent>,
+    time: Res<Time>,
+) {
+    for (e, mut mine, damage, owner, team, transform) in mines.iter_mut() {
+        mine.arm_timer.tick(time.delta());
+        if !mine.arm_timer.finished() {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        // The layer and its teammates would only waste the mine.
+        let triggered = ships.iter().any(|(ship, ship_team, t)| {
+            t.translation.truncate().distance(position) < mine.trigger_radius
+                && rules.can_damage(Some(owner.0), team, ship, ship_team)
+        });
+        if !triggered {
+            continue;
+        }
+
+        for (target, target_team, t) in targets.iter() {
+            let target_position = t.translation.truncate();
+            // Unlike the trigger, the blast has no owner: the layer is only
+            // spared when the rules protect its team.
+            if target_position.distance(position) < mine.blast_radius
+                && rules.can_damage(None, team, target, target_team)
+            {
+                damage_events.send(DamageEvent {
+                    target,
+                    amount: damage.0,
+                    source: Some(owner.0),
+                    position: target_position,
+                });
+            }
+        }
+        pool.release(&mut commands, e);
+    }
+}
+
+fn cool_weapons(mut weapons: Query<&mut Heat>, time: Res<Time>) {
+    for mut heat in weapons.iter_mut() {
+        if heat.current > 0.0 {
+            heat.cool(time.delta_seconds());
         }
     }
 }
@@ -142,36 +864,107 @@ fn fire_weapons(
 fn projectile_life_time(
     mut commands: Commands,
     mut projectiles: Query<(Entity, &mut ProjectileLifeTimer)>,
+    mut pool: ResMut<ProjectilePool>,
     time: Res<Time>,
 ) {
     for (e, mut p) in projectiles.iter_mut() {
         p.0.tick(time.delta());
         if p.0.just_finished() {
-            commands.entity(e).despawn();
+            pool.release(&mut commands, e);
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn collide(
     mut commands: Commands,
     mut collisions: EventReader<CollisionEvent>,
-    projectiles: Query<(&Projectile, &ProjectileDamage)>,
-    mut hp: Query<&mut Hp>,
+    projectiles: Query<
+        (
+            &ProjectileDamage,
+            &Owner,
+            Option<&Team>,
+            &Transform,
+            &Velocity,
+            &Knockback,
+        ),
+        (With<Projectile>, Without<Mine>),
+    >,
+    targets: Query<Option<&Team>, With<Hp>>,
+    mut impulses: Query<&mut ExternalImpulse>,
+    mut pool: ResMut<ProjectilePool>,
+    rules: Res<DamageRules>,
+    mut damage_events: EventWriter<DamageEvent>,
 ) {
     for collision in collisions.iter() {
         info!("{:?}", collision);
         if let CollisionEvent::Started(e0, e1, _) = collision {
-            if let Ok(mut hp) = hp.get_mut(*e0) {
-                if let Ok(p) = projectiles.get(*e1) {
-                    (*hp).0 -= p.1 .0;
-                    commands.entity(*e1).despawn();
-                }
-            } else if let Ok(mut hp) = hp.get_mut(*e1) {
-                if let Ok(p) = projectiles.get(*e0) {
-                    (*hp).0 -= p.1 .0;
-                    commands.entity(*e0).despawn();
+            for (target, projectile) in [(*e0, *e1), (*e1, *e0)] {
+                if let (
+                    Ok(target_team),
+                    Ok((damage, owner, team, transform, velocity, knockback)),
+                ) = (targets.get(target), projectiles.get(projectile))
+                {
+                    if owner.0 == target {
+                        break;
+                    }
+                    if rules.can_damage(Some(owner.0), team, target, target_team) {
+                        damage_events.send(DamageEvent {
+                            target,
+                            amount: damage.0,
+                            source: Some(owner.0),
+                            position: transform.translation.truncate(),
+                        });
+                        if let Ok(mut impulse) = impulses.get_mut(target) {
+                            impulse.impulse += velocity.linvel.normalize_or_zero() * knockback.0;
+                        }
+                    }
+                    pool.release(&mut commands, projectile);
+                    break;
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heat_overheats_at_max_until_cooled_down() {
+        let mut heat = Heat::new(10.0, 4.0, 5.0);
+        heat.increase(4.0);
+        heat.increase(4.0);
+        assert!(!heat.overheated);
+        heat.increase(4.0);
+        assert!(heat.overheated);
+        assert_eq!(heat.current, 10.0);
+
+        heat.cool(1.0);
+        assert!(heat.overheated);
+        heat.cool(1.0);
+        assert!(!heat.overheated);
+        assert_eq!(heat.current, 0.0);
+    }
+
+    #[test]
+    fn fire_rate_change_keeps_reload_progress() {
+        let mut fire_rate = FireRate::new(2.0);
+        fire_rate.timer_mut().tick(Duration::from_millis(200));
+        fire_rate.set_rate(4.0);
+        assert_eq!(fire_rate.timer.duration(), Duration::from_millis(250));
+        assert_eq!(fire_rate.timer.elapsed(), Duration::from_millis(200));
+
+        fire_rate.set_rate(10.0);
+        assert_eq!(fire_rate.timer.elapsed(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn heat_is_disabled_without_heat_per_shot() {
+        let mut heat = Heat::new(10.0, 0.0, 5.0);
+        heat.increase(100.0);
+        assert!(!heat.overheated);
+        assert_eq!(heat.current_percentage(), 0.0);
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{Hp, Score, Team},
+    events::{DamageDealt, ShipDestroyed},
+    ship::Ship,
+};
+
+/// The ship that fired a projectile or beam.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Owner(pub Entity);
+
+#[derive(Default)]
+pub struct DamageRules {
+    pub friendly_fire: bool,
+}
+
+impl DamageRules {
+    /// Whether something fired by `owner` of `team` may hurt `target` of `target_team`.
+    pub fn can_damage(
+        &self,
+        owner: Option<Entity>,
+        team: Option<&Team>,
+        target: Entity,
+        target_team: Option<&Team>,
+    ) -> bool {
+        if owner == Some(target) {
+            return false;
+        }
+        match (team, target_team) {
+            (Some(a), Some(b)) if a == b => self.friendly_fire,
+            _ => true,
+        }
+    }
+}
+
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    pub source: Option<Entity>,
+    pub position: Vec2,
+}
+
+/// Applies [`DamageEvent`]s and credits damage and kills to the shooter.
+pub fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut hp: Query<&mut Hp>,
+    mut scores: Query<&mut Score>,
+    ships: Query<Option<&Name>, With<Ship>>,
+    mut dealt: EventWriter<DamageDealt>,
+    mut destroyed: EventWriter<ShipDestroyed>,
+) {
+    for event in events.iter() {
+        let mut hp = match hp.get_mut(event.target) {
+            Ok(hp) => hp,
+            Err(_) => continue,
+        };
+        let was_alive = hp.0 >= 0.0;
+        hp.0 -= event.amount;
+        let killed = was_alive && hp.0 < 0.0 && ships.contains(event.target);
+        dealt.send(DamageDealt {
+            target: event.target,
+            source: event.source,
+            amount: event.amount,
+        });
+        if killed {
+            let name = |e: Entity| {
+                ships
+                    .get(e)
+                    .ok()
+                    .flatten()
+                    .map_or_else(|| "Unknown".to_string(), |n| n.to_string())
+            };
+            destroyed.send(ShipDestroyed {
+                ship: event.target,
+                name: name(event.target),
+                killer: event.source,
+                killer_name: event.source.map(name),
+            });
+        }
+
+        if let Some(mut score) = event.source.and_then(|s| scores.get_mut(s).ok()) {
+            score.damage += event.amount;
+            if killed {
+                score.kills += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+/// Highest `fire_rate`, fire timers count whole milliseconds.
+pub const MAX_FIRE_RATE: f32 = 1000.0;
+
+/// Weapon stats loaded from `*.weapon.ron` files in `assets/weapons`.
+#[derive(Debug, Deserialize, TypeUuid)]
+#[uuid = "5a0e3c38-8f2e-4c36-9a34-7f8d6f1d2b41"]
+#[serde(default)]
+pub struct WeaponDefinition {
+    /// Shots per second.
+    pub fire_rate: f32,
+    pub damage: f32,
+    pub projectile_speed: f32,
+    /// Seconds until a projectile despawns.
+    pub projectile_lifetime: f32,
+    pub projectile_radius: f32,
+    pub projectile_sprite: Option<String>,
+    /// Where projectiles leave the weapon, relative to its mount.
+    pub muzzle_offset: (f32, f32),
+    /// Random deviation of each shot in degrees.
+    pub spread: f32,
+    /// Impulse applied to whatever a projectile hits.
+    pub knockback: f32,
+    /// Energy drained from the ship per shot.
+    pub energy_cost: f32,
+    /// Heat added per shot, or per second for beams. Zero disables heat.
+    pub heat_per_shot: f32,
+    pub max_heat: f32,
+    /// Heat lost per second.
+    pub cooling_rate: f32,
+    /// Projectiles per shot, fanned out evenly over `spread`.
+    pub pellets: u32,
+    /// Fire several shots in quick succession per trigger.
+    pub burst: Option<Burst>,
+    pub projectile: ProjectileKind,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct Burst {
+    /// Shots per burst, including the first one.
+    pub count: u32,
+    /// Seconds between two shots of a burst.
+    pub interval: f32,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub enum ProjectileKind {
+    /// Flies straight until it hits something or expires.
+    Bullet,
+    /// Steers towards the nearest enemy ship within `range`.
+    Homing {
+        /// Degrees per second.
+        turn_rate: f32,
+        range: f32,
+    },
+    /// Stays where it was dropped and explodes once a ship comes close.
+    Mine {
+        /// Seconds until the mine can be triggered.
+        arm_delay: f32,
+        trigger_radius: f32,
+        blast_radius: f32,
+    },
+    /// Hitscan beam hitting the first collider in its way. `damage` and
+    /// `energy_cost` are applied per second while firing.
+    Beam { range: f32, width: f32 },
+}
+
+impl Default for ProjectileKind {
+    fn default() -> Self {
+        Self::Bullet
+    }
+}
+
+impl Default for WeaponDefinition {
+    fn default() -> Self {
+        Self {
+            fire_rate: 1.0,
+            damage: 1.0,
+            projectile_speed: 250.0,
+            projectile_lifetime: 1.5,
+            projectile_radius: 1.0,
+            projectile_sprite: None,
+            muzzle_offset: (0.0, 100.0),
+            spread: 0.0,
+            knockback: 0.0,
+            energy_cost: 0.0,
+            heat_per_shot: 0.0,
+            max_heat: 100.0,
+            cooling_rate: 25.0,
+            pellets: 1,
+            burst: None,
+            projectile: ProjectileKind::Bullet,
+        }
+    }
+}
+
+impl WeaponDefinition {
+    pub fn muzzle_offset(&self) -> Vec3 {
+        Vec3::new(self.muzzle_offset.0, self.muzzle_offset.1, 0.0)
+    }
+
+    /// Rejects values the weapon timers can't run with: negative durations
+    /// and repeating timers of zero length.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut checks = vec![
+            (
+                "fire_rate",
+                self.fire_rate,
+                self.fire_rate > 0.0 && self.fire_rate <= MAX_FIRE_RATE,
+            ),
+            (
+                "projectile_lifetime",
+                self.projectile_lifetime,
+                self.projectile_lifetime >= 0.0,
+            ),
+        ];
+        if let Some(burst) = self.burst {
+            checks.push(("burst.interval", burst.interval, burst.interval > 0.0));
+        }
+        if let ProjectileKind::Mine { arm_delay, .. } = self.projectile {
+            checks.push(("arm_delay", arm_delay, arm_delay >= 0.0));
+        }
+        // Comparisons with NaN are false, so it fails every check.
+        match checks.into_iter().find(|(_, _, valid)| !valid) {
+            Some((name, value, _)) => Err(format!("invalid {}: {}", name, value)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct WeaponDefinitionLoader;
+
+impl AssetLoader for WeaponDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let definition = ron::de::from_bytes::<WeaponDefinition>(bytes)?;
+            definition.validate().map_err(bevy::asset::Error::msg)?;
+            load_context.set_default_asset(LoadedAsset::new(definition));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["weapon.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_that_would_panic_are_rejected() {
+        assert!(WeaponDefinition::default().validate().is_ok());
+        let invalid = [
+            WeaponDefinition {
+                fire_rate: MAX_FIRE_RATE * 2.0,
+                ..Default::default()
+            },
+            WeaponDefinition {
+                projectile_lifetime: -1.0,
+                ..Default::default()
+            },
+            WeaponDefinition {
+                burst: Some(Burst {
+                    count: 3,
+                    interval: 0.0,
+                }),
+                ..Default::default()
+            },
+            WeaponDefinition {
+                projectile: ProjectileKind::Mine {
+                    arm_delay: f32::NAN,
+                    trigger_radius: 50.0,
+                    blast_radius: 100.0,
+                },
+                ..Default::default()
+            },
+        ];
+        for definition in invalid {
+            assert!(definition.validate().is_err(), "{:?}", definition);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_ggrs::{Rollback, RollbackIdProvider};
+use bevy_rapier2d::prelude::*;
+
+use super::{Homing, Knockback, Mine, Owner, Projectile, ProjectileDamage, ProjectileLifeTimer};
+use crate::components::{RoundEntity, Team};
+
+/// Projectiles that hit something or expired, kept around for reuse.
+///
+/// Reusing the entities keeps their rollback ids stable and avoids
+/// spawning and despawning hundreds of entities per second.
+pub struct ProjectilePool {
+    pub enabled: bool,
+    free: Vec<Entity>,
+}
+
+impl Default for ProjectilePool {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            free: Vec::new(),
+        }
+    }
+}
+
+/// Everything that makes an entity behave like a projectile.
+type ProjectileComponents = (
+    Projectile,
+    Owner,
+    Team,
+    Knockback,
+    ProjectileDamage,
+    ProjectileLifeTimer,
+    Homing,
+    Mine,
+    RigidBody,
+    Collider,
+    Sensor,
+    Velocity,
+    ActiveEvents,
+);
+
+impl ProjectilePool {
+    /// Returns a free projectile entity, spawning a new one if the pool is empty.
+    pub fn acquire<'w, 's, 'a>(
+        &mut self,
+        commands: &'a mut Commands<'w, 's>,
+        rip: Option<&mut RollbackIdProvider>,
+    ) -> EntityCommands<'w, 's, 'a> {
+        if let Some(e) = self.free.pop() {
+            return commands.entity(e);
+        }
+        let mut projectile = commands.spawn();
+        projectile.insert(RoundEntity);
+        if let Some(rip) = rip {
+            projectile.insert(Rollback::new(rip.next_id()));
+        }
+        projectile
+    }
+
+    /// Hides a projectile and keeps it for the next shot, or despawns it if
+    /// pooling is disabled.
+    pub fn release(&mut self, commands: &mut Commands, e: Entity) {
+        if !self.enabled {
+            commands.entity(e).despawn();
+            return;
+        }
+        // A projectile can hit two things in the same frame.
+        if self.free.contains(&e) {
+            return;
+        }
+        commands
+            .entity(e)
+            .remove_bundle::<ProjectileComponents>()
+            .insert(Visibility { is_visible: false });
+        self.free.push(e);
+    }
+
+    /// Forgets the pooled entities after they were despawned with the round.
+    pub fn clear(&mut self) {
+        self.free.clear();
+    }
+
+    pub fn free(&self) -> usize {
+        self.free.len()
+    }
+}
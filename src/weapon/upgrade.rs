@@ -0,0 +1,112 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::ship::Ship;
+
+pub const MAX_LEVEL: u8 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Upgrade {
+    FireRate,
+    Damage,
+    ProjectileCount,
+    ProjectileSpeed,
+}
+
+/// Upgrade levels of all weapons of one ship.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UpgradeLevels {
+    pub fire_rate: u8,
+    pub damage: u8,
+    pub projectile_count: u8,
+    pub projectile_speed: u8,
+}
+
+impl UpgradeLevels {
+    pub fn fire_rate_factor(&self) -> f32 {
+        1.0 + 0.2 * self.fire_rate as f32
+    }
+
+    pub fn damage_factor(&self) -> f32 {
+        1.0 + 0.25 * self.damage as f32
+    }
+
+    pub fn projectile_speed_factor(&self) -> f32 {
+        1.0 + 0.15 * self.projectile_speed as f32
+    }
+
+    pub fn extra_projectiles(&self) -> u32 {
+        self.projectile_count as u32
+    }
+
+    pub fn total(&self) -> u8 {
+        self.fire_rate + self.damage + self.projectile_count + self.projectile_speed
+    }
+}
+
+/// Weapon upgrade levels per ship, collected from upgrade orbs.
+#[derive(Default)]
+pub struct WeaponUpgrades {
+    levels: HashMap<Entity, UpgradeLevels>,
+}
+
+impl WeaponUpgrades {
+    pub fn get(&self, ship: Entity) -> UpgradeLevels {
+        self.levels.get(&ship).copied().unwrap_or_default()
+    }
+
+    /// Raises one upgrade of `ship` by a level, up to [`MAX_LEVEL`].
+    pub fn level_up(&mut self, ship: Entity, upgrade: Upgrade) {
+        let levels = self.levels.entry(ship).or_default();
+        let level = match upgrade {
+            Upgrade::FireRate => &mut levels.fire_rate,
+            Upgrade::Damage => &mut levels.damage,
+            Upgrade::ProjectileCount => &mut levels.projectile_count,
+            Upgrade::ProjectileSpeed => &mut levels.projectile_speed,
+        };
+        *level = (*level + 1).min(MAX_LEVEL);
+    }
+
+    pub fn reset(&mut self, ship: Entity) {
+        self.levels.remove(&ship);
+    }
+}
+
+/// Upgrades are lost when a ship is destroyed.
+pub fn reset_destroyed(mut upgrades: ResMut<WeaponUpgrades>, ships: RemovedComponents<Ship>) {
+    for ship in ships.iter() {
+        upgrades.reset(ship);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_stop_at_max_level() {
+        let mut upgrades = WeaponUpgrades::default();
+        let ship = Entity::from_raw(1);
+        for _ in 0..MAX_LEVEL + 2 {
+            upgrades.level_up(ship, Upgrade::Damage);
+        }
+        upgrades.level_up(ship, Upgrade::ProjectileCount);
+
+        let levels = upgrades.get(ship);
+        assert_eq!(levels.damage, MAX_LEVEL);
+        assert_eq!(levels.extra_projectiles(), 1);
+        assert_eq!(levels.total(), MAX_LEVEL + 1);
+        assert_eq!(levels.damage_factor(), 1.75);
+    }
+
+    #[test]
+    fn levels_are_per_ship_and_reset() {
+        let mut upgrades = WeaponUpgrades::default();
+        let (a, b) = (Entity::from_raw(1), Entity::from_raw(2));
+        upgrades.level_up(a, Upgrade::FireRate);
+
+        assert_eq!(upgrades.get(b), UpgradeLevels::default());
+        assert_eq!(upgrades.get(a).fire_rate_factor(), 1.2);
+        upgrades.reset(a);
+        assert_eq!(upgrades.get(a), UpgradeLevels::default());
+    }
+}